@@ -22,30 +22,75 @@ struct Opt {
     /// The output for in which store the compressed dictionary
     #[structopt(short = "o", long = "output-fst", parse(from_os_str))]
     fst_file: PathBuf,
+    
+    /// The input list is already sorted (once lowercased), it is then streamed
+    /// into the fst instead of being loaded in memory
+    #[structopt(long = "pre-sorted")]
+    pre_sorted: bool,
+    
+    /// The words longer than this are left out, as they cannot be played on the board
+    #[structopt(long = "max-len", default_value = "15")]
+    max_len: usize,
+    
+    /// Only log the warnings and errors (the logs go to stderr)
+    #[structopt(short = "q", long = "quiet", conflicts_with = "verbose")]
+    quiet: bool,
+    
+    /// Also log the debug messages (the logs go to stderr)
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
 }
 
 fn main() {
-    simple_logger::SimpleLogger::new().with_level(log::LevelFilter::Info).init().unwrap();
-    
     let opts = Opt::from_args();
     
-    let Opt { list_file, fst_file } = opts;
+    let Opt { list_file, fst_file, pre_sorted, max_len, quiet, verbose } = opts;
+    
+    let level = if quiet {
+        log::LevelFilter::Warn
+    } else if verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    simple_logger::SimpleLogger::new().with_level(level).init().unwrap();
     
-    let start = Instant::now();
     let file = BufReader::new(File::open(list_file).expect("opening the words list file"));
-    let mut words = file.lines().map(|l|
+    let mut n_too_long = 0;
+    let words = file.lines().map(|l|
         l.expect("reading line from word list").trim().to_lowercase()
-    ).collect::<Vec<_>>();
-    log::info!("words loaded in {:?}", Instant::now() - start);
-    
-    let start = Instant::now();
-    words.sort_unstable();
-    log::info!("words sorted in {:?}", Instant::now() - start);
+    ).filter(|word| {
+        let fits = word.len() <= max_len;
+        if !fits {
+            n_too_long += 1;
+        }
+        fits
+    });
     
-    let start = Instant::now();
     let wtr = BufWriter::new(File::create(fst_file).expect("create the words fst file"));
     let mut build = SetBuilder::new(wtr).expect("builder wrting to fst file");
-    build.extend_iter(words).unwrap();
-    build.finish().unwrap();
-    log::info!("dictionary written in {:?}", Instant::now() - start);
+    
+    if pre_sorted {
+        let start = Instant::now();
+        build.extend_iter(words).expect("the word list is not sorted");
+        build.finish().unwrap();
+        log::info!("dictionary streamed in {:?}", Instant::now() - start);
+    } else {
+        let start = Instant::now();
+        let mut words = words.collect::<Vec<_>>();
+        log::info!("words loaded in {:?}", Instant::now() - start);
+        
+        let start = Instant::now();
+        words.sort_unstable();
+        log::info!("words sorted in {:?}", Instant::now() - start);
+        
+        let start = Instant::now();
+        build.extend_iter(words).unwrap();
+        build.finish().unwrap();
+        log::info!("dictionary written in {:?}", Instant::now() - start);
+    }
+    
+    if n_too_long > 0 {
+        log::warn!("{} words longer than {} letters were left out", n_too_long, max_len);
+    }
 }
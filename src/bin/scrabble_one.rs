@@ -8,11 +8,13 @@ use std::io::{
 };
 use std::convert::TryInto;
 use std::time::Instant;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
+use scrabble::PositionFormat;
+
 #[derive(Debug)]
 enum FileOrString {
     File(PathBuf),
@@ -20,12 +22,22 @@ enum FileOrString {
 }
 
 impl FileOrString {
-    pub fn read_to_string(self) -> std::io::Result<String> {
+    /// Reads the content, where `-` (as a string or as a file) means the standard input
+    pub fn read_to_string(&self) -> std::io::Result<String> {
         match self {
-            Self::String(s) => Ok(s),
+            Self::String(s) if s == "-" => Self::read_stdin(),
+            Self::File(f) if f.as_os_str() == "-" => Self::read_stdin(),
+            Self::String(s) => Ok(s.clone()),
             Self::File(f) => std::fs::read_to_string(f),
         }
     }
+    
+    fn read_stdin() -> std::io::Result<String> {
+        use std::io::Read;
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s)?;
+        Ok(s)
+    }
 }
 
 impl<'de> serde::Deserialize<'de> for FileOrString {
@@ -79,43 +91,160 @@ struct Settings {
     /// Either a `.txt` file with one word per line, or a `.fst` file generated with `make_fst`
     dictionary: PathBuf,
     
-    /// The board as a string or the file containing it (more info in `Opt`)
-    board: FileOrString,
+    /// Whether the `.txt` dictionary is already sorted (once lowercased), so
+    /// that it is streamed into the fst instead of being sorted in memory
+    #[serde(default)]
+    pre_sorted: bool,
+    
+    /// Other word lists that can be played, by name, in the same format as `dictionary`
+    #[serde(default)]
+    sources: BTreeMap<String, PathBuf>,
+    
+    /// Whether the word lists (`dictionary` and `sources`) containing each word are shown
+    #[serde(default)]
+    show_source: bool,
+    
+    /// The words that can be formed in the perpendicular direction of the move,
+    /// in the same format as `dictionary`. Not present means `dictionary` is used
+    cross_dictionary: Option<PathBuf>,
+    
+    /// A stricter word list, in the same format as `dictionary`, used by `safe_only`
+    strict_dictionary: Option<PathBuf>,
+    
+    /// Whether only the moves whose words are all in `strict_dictionary` are shown
+    #[serde(default)]
+    safe_only: bool,
     
-    /// The tray as a string or the file containing it (more info in `Opt`)
-    tray: FileOrString,
+    /// The board as a string or the file containing it (more info in `Opt`), not needed by `server_stdin`
+    board: Option<FileOrString>,
+    
+    /// The tray as a string or the file containing it (more info in `Opt`), not needed by `server_stdin`
+    tray: Option<FileOrString>,
+    
+    /// Whether the positions are read from the standard input, one JSON line per position,
+    /// each answered with one JSON line
+    #[serde(default)]
+    server_stdin: bool,
+    
+    /// The direction of the moves (`h` or `v`) whose cross-checks are shown instead of the moves
+    dump_crosschecks: Option<String>,
+    
+    /// The characters that are empty squares in the board, spaces and underscores if not present
+    empty_chars: Option<String>,
     
     /// The number of top result shown, not present means all results are shown
     n_shown: Option<usize>,
     
+    /// The moves scoring less are not shown
+    #[serde(default)]
+    min_score: u32,
+    
+    /// The maximum number of moves kept in memory, the ones with the lowest scores are dropped
+    max_moves: Option<usize>,
+    
+    /// The number of placements explored by each parallel task, not present means the default of the library
+    placements_per_task: Option<usize>,
+    
+    /// The game variant giving the letter scores, the bonuses of the board and `extra_bonus`,
+    /// one of `scrabble::presets::NAMES`. Not present means English Scrabble
+    variant: Option<String>,
+    
+    /// A file with the whole definition of a game (see `scrabble::presets::Ruleset`), replacing `variant`
+    ruleset: Option<PathBuf>,
+    
+    /// The score of each letter, replacing the one of `variant`
     letter_score: Option<HashMap<char, u32>>,
     
     #[serde(default)]
     wildcards_have_multi_meaning: bool,
     
-    #[serde(default = "fifty")]
-    extra_bonus: u32,
+    /// The minimum length of the word played in the direction of the move
+    #[serde(default)]
+    min_main_word_len: usize,
+    
+    /// The letters of the tray that each move must play (more info in `Opt`)
+    #[serde(default)]
+    must_play: String,
+    
+    /// Whether only the moves playing all the tiles of the tray are shown
+    #[serde(default)]
+    bingos_only: bool,
+    
+    /// The tiles of the tray that the moves keep for a later turn (more info in `Opt`)
+    #[serde(default)]
+    hold: String,
+    
+    /// The most tiles of the tray a move can play, not present means no limit
+    max_tiles_placed: Option<usize>,
+    
+    /// Words of the dictionary that cannot be played
+    #[serde(default)]
+    excluded_words: Vec<String>,
+    
+    /// The rank of the only move shown, 1 being the best move (more info in `Opt`)
+    nth: Option<usize>,
+    
+    /// The word to find the best move for, instead of showing all the moves (more info in `Opt`)
+    word: Option<String>,
+    
+    /// Whether the moves must touch a tile of the board, not present means true
+    require_attachment: Option<bool>,
+    
+    /// The opposite corners of the part of the board the moves are played in (more info in `Opt`),
+    /// not present means the whole board
+    region: Option<String>,
+    
+    /// Whether the move is the first of the game and must cover the center, not present means
+    /// only on an empty board
+    first_move: Option<bool>,
+    
+    /// The bonus of a bingo, not present means the one of `variant`
+    extra_bonus: Option<u32>,
+    
+    /// The cap of the multiplier of the main word, not present means no cap
+    max_word_multiplier: Option<u32>,
+    
+    /// Whether the word bonuses covered by a word are multiplied or added
+    #[serde(default)]
+    premium_mode: scrabble::score_rules::PremiumMode,
+    
+    /// Whether the wildcards score as the letter they stand for
+    #[serde(default)]
+    blank_scores_as_letter: bool,
     
     #[serde(default)]
     position_format: PositionFormat,
     
     #[serde(default)]
     show_each_score: bool,
-}
-
-fn fifty() -> u32 { 50 }
-
-#[derive(Debug, Clone, Copy, serde::Deserialize)]
-#[serde(field_identifier, rename_all = "snake_case")]
-enum PositionFormat {
-    LetterDigit,
-    DigitLetter,
-}
-
-impl Default for PositionFormat {
-    fn default() -> Self {
-        Self::LetterDigit
-    }
+    
+    /// Whether the number of vowels and consonants left in the tray is shown after each move
+    #[serde(default)]
+    show_leave_balance: bool,
+    
+    /// The letters counted as vowels by `show_leave_balance`, `aeiou` if not present
+    vowels: Option<String>,
+    
+    /// Whether the moves are ranked by their score plus the value of the tiles they leave in the tray
+    #[serde(default)]
+    equity: bool,
+    
+    /// How much the `exposure_penalty` of a move counts to rank the moves of equal equity,
+    /// 0 if not present to leave it out
+    #[serde(default)]
+    exposure_weight: f64,
+    
+    /// Whether only the moves playing a two-letter word are shown, with the words they form
+    #[serde(default)]
+    twos: bool,
+    
+    /// Whether the vertical moves are hidden on an empty board, as they are the same as the horizontal ones
+    #[serde(default)]
+    hide_transposed_first_moves: bool,
+    
+    /// Whether unknown characters in the board or the tray, and letters without score, are errors instead of warnings
+    #[serde(default)]
+    strict: bool,
 }
 
 #[derive(Debug, StructOpt)]
@@ -132,17 +261,92 @@ struct Opt {
     dict: Option<String>,
     
     /// The board, where one line in the file corresponds to one row of the board.
-    /// Spaces and underscores are interpreted as empty squares, and stars as wildcards
+    /// Spaces and underscores are interpreted as empty squares, and stars as wildcards.
+    /// `-` reads the board from the standard input
     #[structopt(short = "b", long = "board")]
     board_file: Option<String>,
     
-    /// The tray, a string of the letters contained in the tray, where stars are interpreted as wildcards
+    /// The tray, a string of the letters contained in the tray, where stars are interpreted as wildcards.
+    /// `-` reads the tray from the standard input
     #[structopt(short = "t", long = "tray")]
     tray_string: Option<String>,
     
+    /// A file (like `ruleset.json`) with the letter scores, the bonuses of the board and the bingo bonus of the game
+    #[structopt(long = "ruleset")]
+    ruleset: Option<String>,
+    
     /// The number of top result shown, not present means all results are shown
     #[structopt(short = "n", long = "number-shown")]
     n_shown: Option<usize>,
+    
+    /// Only show the moves scoring at least this, combined with `--number-shown` if both are given
+    #[structopt(long = "min-score")]
+    min_score: Option<u32>,
+    
+    /// The letters of the tray that each move must play, repeated to require several of them (for example `ss`).
+    /// Wildcards used as these letters do not count
+    #[structopt(long = "must-play")]
+    must_play: Option<String>,
+    
+    /// The tiles of the tray that the moves must not play, to keep them for a later turn (for example `st`).
+    /// The leaves still count them
+    #[structopt(long = "hold")]
+    hold: Option<String>,
+    
+    /// Keep the dictionary loaded and solve the positions read from the standard input,
+    /// one JSON `{"board": ..., "tray": ...}` per line, each answered with one JSON line
+    #[structopt(long = "server-stdin")]
+    server_stdin: bool,
+    
+    /// Show the letters that the moves in this direction (`h` or `v`) can place on each square,
+    /// because of the words they form in the other direction, instead of the moves
+    #[structopt(long = "dump-crosschecks", possible_values = &["h", "v"])]
+    dump_crosschecks: Option<String>,
+    
+    /// Only show the move with this rank, 1 being the best move and 2 the second best
+    #[structopt(long = "nth")]
+    nth: Option<usize>,
+    
+    /// Only show the best move playing this word, using the tiles of the board and wildcards if needed
+    #[structopt(long = "word")]
+    word: Option<String>,
+    
+    /// Show which word lists (`dictionary` and `sources` in the config) contain each word
+    #[structopt(long = "show-source")]
+    show_source: bool,
+    
+    /// Only show the moves whose words are all in `strict_dictionary` of the config
+    #[structopt(long = "safe-only")]
+    safe_only: bool,
+    
+    /// Rank the moves by their equity, the score plus the value of the tiles left in the tray
+    #[structopt(long = "equity")]
+    equity: bool,
+    
+    /// Only show the moves playing a two-letter word in their direction, with all the words they form
+    #[structopt(long = "twos")]
+    twos: bool,
+    
+    /// Only show the moves playing all the tiles of the tray (bingos)
+    #[structopt(long = "bingos-only")]
+    bingos_only: bool,
+    
+    /// Only show the moves playing at most this number of tiles of the tray
+    #[structopt(long = "max-tiles")]
+    max_tiles: Option<usize>,
+    
+    /// Only show the moves whose word fits in the part of the board between two opposite corners,
+    /// written in the `position_format` of the config (for example `a1:g7`)
+    #[structopt(long = "region")]
+    region: Option<String>,
+    
+    /// Only log the warnings and errors (the logs go to stderr)
+    #[structopt(short = "q", long = "quiet", conflicts_with = "verbose")]
+    quiet: bool,
+    
+    /// Also log the debug messages, by default the level is given by `RUST_LOG` (the logs go to stderr)
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
 }
 
 fn load_config(opt: Opt) -> Result<Settings, config::ConfigError> {
@@ -163,184 +367,306 @@ fn load_config(opt: Opt) -> Result<Settings, config::ConfigError> {
     if let Some(t) = opt.tray_string {
         s.set("tray", t)?;
     }
+    if let Some(r) = opt.ruleset {
+        s.set("ruleset", r)?;
+    }
     if let Some(n) = opt.n_shown {
         s.set::<i64>("n_shown", n.try_into().unwrap())?;
     }
+    if let Some(m) = opt.min_score {
+        s.set::<i64>("min_score", m.into())?;
+    }
+    if let Some(k) = opt.nth {
+        s.set::<i64>("nth", k.try_into().unwrap())?;
+    }
+    if let Some(w) = opt.word {
+        s.set("word", w)?;
+    }
+    if let Some(m) = opt.must_play {
+        s.set("must_play", m)?;
+    }
+    if let Some(h) = opt.hold {
+        s.set("hold", h)?;
+    }
+    if opt.bingos_only {
+        s.set("bingos_only", true)?;
+    }
+    if let Some(m) = opt.max_tiles {
+        s.set::<i64>("max_tiles_placed", m.try_into().unwrap())?;
+    }
+    if let Some(r) = opt.region {
+        s.set("region", r)?;
+    }
+    if opt.show_source {
+        s.set("show_source", true)?;
+    }
+    if opt.safe_only {
+        s.set("safe_only", true)?;
+    }
+    if opt.equity {
+        s.set("equity", true)?;
+    }
+    if opt.twos {
+        s.set("twos", true)?;
+    }
+    if let Some(d) = opt.dump_crosschecks {
+        s.set("dump_crosschecks", d)?;
+    }
+    if opt.server_stdin {
+        s.set("server_stdin", true)?;
+    }
     
     s.try_into()
 }
 
 fn main() {
-    simple_logger::SimpleLogger::from_env().init().unwrap();
-    
     let opt = Opt::from_args();
     
-    let conf = load_config(opt).expect("config");
+    let logger = simple_logger::SimpleLogger::new().env();
+    if opt.quiet {
+        logger.with_level(log::LevelFilter::Warn)
+    } else if opt.verbose {
+        logger.with_level(log::LevelFilter::Debug)
+    } else {
+        logger
+    }.init().unwrap();
     
-    let board = conf.board.read_to_string().expect("read board");
-    let tray = conf.tray.read_to_string().expect("read tray");
-    let n_shown = conf.n_shown;
-    let wildcards_have_multi_meaning = conf.wildcards_have_multi_meaning;
-    let extra_bonus = conf.extra_bonus;
-    let position_format = conf.position_format;
-    let show_each_score = conf.show_each_score;
+    let conf = load_config(opt).expect("config");
     
-    let dict = conf.dictionary;
+    let mut dictionary = load_dictionary(&conf.dictionary, conf.pre_sorted);
+    let mut sources = vec![];
+    if conf.show_source || !conf.sources.is_empty() {
+        let name = conf.dictionary.file_stem().map_or("dictionary".into(), |stem| stem.to_string_lossy());
+        sources.push((name.into_owned(), dictionary));
+        for (name, path) in &conf.sources {
+            sources.push((name.clone(), load_dictionary(path, conf.pre_sorted)));
+        }
+        dictionary = scrabble::union_dictionaries(sources.iter().map(|(_, d)| d));
+        log::info!("word lists merged: {} words", dictionary.len());
+    }
+    let cross_dictionary = conf.cross_dictionary.as_ref().map(|d| load_dictionary(d, conf.pre_sorted));
+    
+    if let Some(ruleset) = &conf.ruleset {
+        let mut s = config::Config::new();
+        s.merge(config::File::from(ruleset.as_path())).expect("reading the ruleset file");
+        let ruleset: scrabble::presets::Ruleset = s.try_into().expect("ruleset");
+        let preset = ruleset.into_preset().expect("invalid ruleset");
+        return main_with_variant(dictionary, cross_dictionary, preset, &sources, &conf);
+    }
     
-    match dict.extension().and_then(|s| s.to_str()) {
+    match conf.variant.as_deref().unwrap_or("scrabble_english") {
+        "scrabble_english" => main_with_variant(dictionary, cross_dictionary, scrabble::presets::scrabble_english(), &sources, &conf),
+        "words_with_friends" => main_with_variant(dictionary, cross_dictionary, scrabble::presets::words_with_friends(), &sources, &conf),
+        "french_scrabble" => main_with_variant(dictionary, cross_dictionary, scrabble::presets::french_scrabble(), &sources, &conf),
+        "super_scrabble" => main_with_variant(dictionary, cross_dictionary, scrabble::presets::super_scrabble(), &sources, &conf),
+        other => panic!("unknown variant {:?}, expected one of {:?}", other, scrabble::presets::NAMES),
+    }
+}
+
+fn main_with_variant<D: AsRef<[u8]> + Sync>(
+    dict: fst::Set<D>,
+    cross_dict: Option<fst::Set<D>>,
+    preset: scrabble::presets::Preset<impl scrabble::LetterScoring, impl scrabble::score_rules::BoardBonus>,
+    sources: &[(String, Set<Vec<u8>>)],
+    conf: &Settings,
+) {
+    let board_size = (preset.rows, preset.cols);
+    let score_rules = preset.score_rules;
+    let extra_bonus = conf.extra_bonus.unwrap_or(score_rules.extra_bonus);
+    
+    if let Some(letter_score) = &conf.letter_score {
+        main_with_dict(
+            dict, cross_dict,
+            SimpleLetterScore { map: letter_score.clone() },
+            score_rules.bonuses, extra_bonus, score_rules.rack_size,
+            board_size, sources, conf,
+        )
+    } else {
+        main_with_dict(
+            dict, cross_dict,
+            score_rules.scoring,
+            score_rules.bonuses, extra_bonus, score_rules.rack_size,
+            board_size, sources, conf,
+        )
+    }
+}
+
+fn load_dictionary(dict: &Path, pre_sorted: bool) -> Set<Vec<u8>> {
+    let dictionary = match dict.extension().and_then(|s| s.to_str()) {
         Some("fst") => {
             let start = Instant::now();
             let data = std::fs::read(dict).expect("reading the words fst file");
             let dictionary = Set::new(data).expect("converting fst file in set");
             log::info!("dictionary loaded in {:?}", Instant::now() - start);
-            
-            if let Some(letter_score) = conf.letter_score {
-                main_with_dict(
-                    dictionary,
-                    board,
-                    tray,
-                    n_shown,
-                    SimpleLetterScore { map: letter_score },
-                    wildcards_have_multi_meaning,
-                    extra_bonus,
-                    position_format,
-                    show_each_score,
-                )
-            } else {
-                main_with_dict(
-                    dictionary,
-                    board,
-                    tray,
-                    n_shown,
-                    scrabble::score_rules::EnglishScrabbleScoring,
-                    wildcards_have_multi_meaning,
-                    extra_bonus,
-                    position_format,
-                    show_each_score,
-                )
-            }
+            dictionary
         },
         Some("txt") => {
-            let start = Instant::now();
             let file = BufReader::new(File::open(dict).expect("opening the words list file"));
-            let mut words = file.lines().map(|l|
-                l.expect("reading line from word list").trim().to_lowercase()
-            ).collect::<Vec<_>>();
-            log::info!("words loaded in {:?}", Instant::now() - start);
-            
-            let start = Instant::now();
-            words.sort_unstable();
-            log::info!("words sorted in {:?}", Instant::now() - start);
             
-            let start = Instant::now();
             let mut build = SetBuilder::memory();
-            build.extend_iter(words).unwrap();
-            let dictionary = build.into_set();
-            log::info!("dictionary build in {:?}", Instant::now() - start);
-            
-            if let Some(letter_score) = conf.letter_score {
-                main_with_dict(
-                    dictionary,
-                    board,
-                    tray,
-                    n_shown,
-                    SimpleLetterScore { map: letter_score },
-                    wildcards_have_multi_meaning,
-                    extra_bonus,
-                    position_format,
-                    show_each_score,
-                )
+            if pre_sorted {
+                let words = file.lines().map(|l|
+                    l.expect("reading line from word list").trim().to_lowercase()
+                );
+                let start = Instant::now();
+                build.extend_iter(words).expect("the word list is not sorted");
+                log::info!("dictionary streamed in {:?}", Instant::now() - start);
             } else {
-                main_with_dict(
-                    dictionary,
-                    board,
-                    tray,
-                    n_shown,
-                    scrabble::score_rules::EnglishScrabbleScoring,
-                    wildcards_have_multi_meaning,
-                    extra_bonus,
-                    position_format,
-                    show_each_score,
-                )
+                use rayon::prelude::*;
+                
+                let start = Instant::now();
+                let lines = file.lines().collect::<Result<Vec<_>, _>>().expect("reading line from word list");
+                let mut words = lines.par_iter().map(|l| l.trim().to_lowercase()).collect::<Vec<_>>();
+                log::info!("words loaded in {:?}", Instant::now() - start);
+                
+                let start = Instant::now();
+                words.par_sort_unstable();
+                log::info!("words sorted in {:?}", Instant::now() - start);
+                
+                let start = Instant::now();
+                build.extend_iter(words).unwrap();
+                log::info!("dictionary build in {:?}", Instant::now() - start);
             }
+            build.into_set()
         },
         _ => {
             panic!("dictionary file is neither .txt of .fst")
         },
-    }
+    };
+    log::info!("dictionary loaded: {} words", dictionary.len());
+    dictionary
 }
 
-fn main_with_dict(
-    dict: fst::Set<impl AsRef<[u8]> + Sync>,
-    board_string: String,
-    tray_string: String,
-    n_shown: Option<usize>,
+#[allow(clippy::too_many_arguments)]
+fn main_with_dict<D: AsRef<[u8]> + Sync>(
+    dict: fst::Set<D>,
+    cross_dict: Option<fst::Set<D>>,
     letter_score: impl scrabble::LetterScoring,
-    wildcards_have_multi_meaning: bool,
+    bonuses: impl scrabble::score_rules::BoardBonus,
     extra_bonus: u32,
-    position_format: PositionFormat,
-    show_each_score: bool,
+    rack_size: usize,
+    (rows, cols): (usize, usize),
+    sources: &[(String, Set<Vec<u8>>)],
+    conf: &Settings,
 ) {
-    
     use scrabble::{
         Board,
+        BoardFormat,
         Letter,
         LetterTile,
-        Position,
-        Square,
+        Strictness,
         solver::{
             arenas::Arenas,
             StrList,
             word_finder::TrayRemaining,
-            evaluate,
+            evaluate_with_granularity,
+            is_transposed_first_move,
         },
     };
     
+    let n_shown = conf.n_shown;
+    let position_format = conf.position_format;
+    let show_each_score = conf.show_each_score;
+    let strictness = if conf.strict { Strictness::Strict } else { Strictness::Lenient };
+    
+    let board_format = match &conf.empty_chars {
+        Some(empty_chars) => BoardFormat::with_empty_chars(empty_chars.as_bytes()).expect("empty_chars must not contain letters or wildcards"),
+        None => BoardFormat::default(),
+    };
+    
+    let must_play = conf.must_play.bytes().map(|byte| {
+        assert!(byte.is_ascii_alphabetic(), "must_play contains something else than letters: {}", byte);
+        Letter(byte.to_ascii_lowercase())
+    }).collect();
+    
+    let region = conf.region.as_ref().map(|region| {
+        let (first, second) = region.split_once(':').expect("region must be two corners separated by `:`");
+        let parse = |corner: &str| position_format.parse(corner).unwrap_or_else(|| panic!("invalid corner of region: {}", corner));
+        let (first, second) = (parse(first), parse(second));
+        (
+            scrabble::Position { row: first.row.min(second.row), col: first.col.min(second.col) },
+            scrabble::Position { row: first.row.max(second.row), col: first.col.max(second.col) },
+        )
+    });
+    
+    use scrabble::score_rules::ScoreRules;
+    use scrabble::Rules;
+    
+    // the bonuses are still needed by the ranking once the moves are found
+    let mut score_rules = ScoreRules::new(letter_score, &bonuses, extra_bonus);
+    score_rules.rack_size = rack_size;
+    score_rules.max_word_multiplier = conf.max_word_multiplier;
+    score_rules.premium_mode = conf.premium_mode;
+    score_rules.blank_scores_as_letter = conf.blank_scores_as_letter;
+    
+    let rules = Rules {
+        score_rules,
+        wildcards_have_multi_meaning: conf.wildcards_have_multi_meaning,
+        min_main_word_len: conf.min_main_word_len,
+        must_play,
+        bingos_only: conf.bingos_only,
+        max_tiles_placed: conf.max_tiles_placed,
+        min_score: conf.min_score,
+        dictionary: dict,
+        cross_dictionary: cross_dict,
+        exclude: conf.excluded_words.iter().map(|w| w.trim().to_lowercase()).collect(),
+        require_attachment: conf.require_attachment.unwrap_or(true),
+        region,
+        first_move: conf.first_move,
+    };
+    
+    if conf.server_stdin {
+        let mut context = scrabble::solver::SolverContext::new(rules);
+        context.max_moves = conf.max_moves.unwrap_or(usize::MAX);
+        context.placements_per_task = conf.placements_per_task.unwrap_or(scrabble::solver::DEFAULT_PLACEMENTS_PER_TASK);
+        return serve_stdin(&context, (rows, cols), &board_format, strictness, conf);
+    }
+    
     let start = Instant::now();
     
-    // fill tray
-    let mut letters = [0u8; 256];
-    let mut wild_count = 0;
-    
-    for byte in tray_string.bytes() {
-        if byte.is_ascii_alphabetic() {
-            letters[byte.to_ascii_lowercase() as usize] += 1;
-        } else if byte == b'*' {
-            wild_count += 1;
-        } else {
-            log::warn!("a byte in the given tray is neither a letter or a wildcard (*): {}", byte);
-        }
+    let board_string = conf.board.as_ref().expect("no board given").read_to_string().expect("read board");
+    let board = Board::parse_with_size(&board_string, rows, cols, &board_format, strictness).expect("invalid board");
+    
+    if let Some(dir) = &conf.dump_crosschecks {
+        let dir = match dir.as_str() {
+            "h" => scrabble::Direction::Horizontal,
+            "v" => scrabble::Direction::Vertical,
+            _ => panic!("dump_crosschecks must be `h` or `v`, not {:?}", dir),
+        };
+        let checks = scrabble::solver::cross_checks(&board, dir, rules.cross_dictionary(), &rules.exclude);
+        print!("{}", scrabble::solver::render_board_cross_checks(&board, &checks));
+        return
     }
     
-    let tray = TrayRemaining::new(letters, wild_count);
-    
-    // fill board
-    let mut board = Board::empty();
-    let file = BufReader::new(board_string.as_bytes());
-    file.lines().enumerate().for_each(|(i, line)| {
-        let line = line.expect("reading board line");
-        line.bytes().enumerate().for_each(|(j, byte)| {
-            let (
-                letter_tile,
-                value_tile,
-            ) = if byte.is_ascii_alphabetic() {
-                let t = LetterTile::Letter(Letter(byte.to_ascii_lowercase()));
-                (t, if byte.is_ascii_uppercase() {LetterTile::Wildcard} else {t})
-            } else if byte == b'*' {
-                (LetterTile::Wildcard, LetterTile::Wildcard)
-            } else if byte == b' ' || byte == b'_' {
-                return // leave empty
-            } else {
-                log::warn!("a byte in the given board is neither a letter, a wildcard (*), or empty ( _): {}", byte);
-                return
-            };
-            board.letter_table.set(Position { row: i, col: j }, Square::Filled(letter_tile));
-            board.value_table.set(Position { row: i, col: j }, Square::Filled(value_tile));
-        })
-    });
+    let tray_string = conf.tray.as_ref().expect("no tray given").read_to_string().expect("read tray");
+    let tray = TrayRemaining::parse(&tray_string, strictness).expect("unknown characters in the tray");
+    
+    // the held tiles are left out of the search, but stay in the leaves
+    let held = TrayRemaining::parse(&conf.hold, strictness).expect("unknown characters in hold");
+    let playable = tray.without(&held);
+    
+    if strictness == Strictness::Strict {
+        scrabble::score_rules::check_scores(&rules.score_rules.scoring, &board, &tray).expect("tiles without score");
+    }
     
     log::info!("board info loaded in {:?}", Instant::now() - start);
     
+    if let Some(word) = &conf.word {
+        match scrabble::solver::best_placement_for_word(&playable, &board, rules, word) {
+            Some((mov, score)) => println!("{:>3}: {:<23} {:?}", score, mov.as_move().format(position_format), [word.to_lowercase()]),
+            None => log::warn!("{:?} cannot be played with this tray", word),
+        }
+        return
+    }
+    
+    if conf.twos {
+        let plays = scrabble::solver::two_letter_plays(&board, &playable, &rules);
+        for (mov, score, words) in plays.into_iter().rev().take(n_shown.unwrap_or(usize::MAX)) {
+            println!("{:>3}: {:<23} {:?}", score, mov.as_move().format(position_format), words);
+        }
+        return
+    }
+    
     // evaluate
     
     let arenas_str: Arenas<u8> = Arenas::new();
@@ -349,30 +675,86 @@ fn main_with_dict(
     
     let start = Instant::now();
     
-    use scrabble::score_rules::{ScoreRules, ScrabbleBonus};
-    use scrabble::Rules;
+    let mut result = evaluate_with_granularity(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        &playable, &board,
+        rules,
+        conf.max_moves.unwrap_or(usize::MAX),
+        conf.placements_per_task.unwrap_or(scrabble::solver::DEFAULT_PLACEMENTS_PER_TASK),
+    );
+    
+    log::info!("scores evaluated in {:?} ({} possible moves)", Instant::now() - start, result.score.len());
+    #[cfg(feature = "profile")]
+    log::info!("automaton: {:?}", result.stats);
+    if result.truncated {
+        log::warn!("only the {} best moves were kept", result.score.len());
+    }
+    if result.score.is_empty() {
+        log::warn!("no move can be played with this tray, the only options are to exchange tiles or to pass");
+    }
+    
+    if conf.hide_transposed_first_moves {
+        result.score.retain(|(mov, _)| !is_transposed_first_move(&board, mov));
+    }
+    
+    if conf.safe_only {
+        let strict = conf.strict_dictionary.as_ref().expect("safe_only needs a strict_dictionary");
+        let strict = load_dictionary(strict, conf.pre_sorted);
+        result.score = result.mark_safe(&board, &strict).into_iter()
+            .filter(|(_, _, safe)| *safe)
+            .map(|(mov, score, _)| (mov, score))
+            .collect();
+    }
+    
+    if let Some(k) = conf.nth {
+        let nth = result.nth_best(k).cloned();
+        if nth.is_none() {
+            log::warn!("there is no move number {}, {} moves can be played", k, result.score.len());
+        }
+        result.score = nth.into_iter().collect();
+    }
+    
+    let equities = if conf.equity {
+        Some(scrabble::solver::rank_by_equity_and_exposure(
+            &result, &board, &tray, scrabble::solver::leave_value,
+            &bonuses, conf.exposure_weight,
+        ))
+    } else {
+        None
+    };
     
     let scrabble::solver::EvaluationResult {
         words: found_moves,
         score: score_per_move,
-    } = evaluate(
-        &arenas_str, &arenas_str_list, &arenas_mov,
-        &tray, &board,
-        Rules {
-            score_rules: ScoreRules {
-                scoring: letter_score,
-                bonuses: ScrabbleBonus,
-                extra_bonus,
-            },
-            wildcards_have_multi_meaning,
-            dictionary: dict,
-        },
-    );
-    
-    log::info!("scores evaluated in {:?} ({} possible moves)", Instant::now() - start, score_per_move.len());
+        ..
+    } = result;
     
     // print moves
     
+    let vowels = conf.vowels.as_deref().unwrap_or("aeiou").to_lowercase();
+    let word_sources = |words: Vec<&str>| if conf.show_source {
+        words.iter()
+            .map(|word| format!(" ({}: {})", word, scrabble::word_sources(word, sources).join(" ")))
+            .collect()
+    } else {
+        String::new()
+    };
+    let leave_balance = |mov: &scrabble::Move| if conf.show_leave_balance {
+        let leave = tray.after_move(mov).expect("the move plays tiles that are not in the tray");
+        let (n_vowels, n_consonants) = leave.vowel_consonant_counts_with(vowels.as_bytes());
+        format!(" (leave: {} vowels, {} consonants)", n_vowels, n_consonants)
+    } else {
+        String::new()
+    };
+    
+    if let Some(ranked) = equities {
+        for (mov, score, equity) in ranked.into_iter().rev().take(n_shown.unwrap_or(usize::MAX)) {
+            let words = found_moves.get(&mov).unwrap();
+            println!("{:>6.1} ({:>3}): {:<23} {:?}{}{}", equity, score, mov.format(position_format), words, word_sources(words.to_vec()), leave_balance(&mov));
+        }
+        return
+    }
+    
     let mut last_score = None;
     if let Some(n) = n_shown {
         for (mov, score) in score_per_move.into_iter().rev().take(n) {
@@ -382,7 +764,7 @@ fn main_with_dict(
                 last_score = Some(score);
                 print!("{:>3}: ", score)
             }
-            println!("{:<23} {:?}", format_move(&mov, position_format), found_moves.get(&mov).unwrap());
+            println!("{:<23} {:?}{}{}", mov.format(position_format), found_moves.get(&mov).unwrap(), word_sources(found_moves.get(&mov).unwrap().to_vec()), leave_balance(&mov));
         }
     } else {
         for (mov, score) in score_per_move.into_iter().rev() {
@@ -392,48 +774,64 @@ fn main_with_dict(
                 last_score = Some(score);
                 print!("{:>3}: ", score)
             }
-            println!("{:<23} {:?}", format_move(&mov, position_format), found_moves.get(&mov).unwrap());
+            println!("{:<23} {:?}{}{}", mov.format(position_format), found_moves.get(&mov).unwrap(), word_sources(found_moves.get(&mov).unwrap().to_vec()), leave_balance(&mov));
         }
     }
 }
 
-fn format_move(
-    mov: &scrabble::Move,
-    position_format: PositionFormat,
-) -> String {
-    use scrabble::{Direction, Move::*};
-    match mov {
-        SingleLetter(pos, tile) => {
-            format!(
-                "{},   {}",
-                position_format.format(pos),
-                tile_to_char(tile)
-            )
-        },
-        MultiLetters(place, first, others) => {
-            format!(
-                "{} {}, {}",
-                position_format.format(&place.0),
-                match place.1 {
-                    Direction::Horizontal => "→",
-                    Direction::Vertical => "↓",
-                },
-                std::iter::once(tile_to_char(first)).chain(
-                    others.iter().map(|(n, tile)|
-                        std::iter::repeat('_').take(*n).chain(std::iter::once(tile_to_char(tile)))
-                    ).flatten()
-                ).collect::<String>(),
-            )
-        },
-    }
+/// A position read by `serve_stdin`
+#[derive(serde::Deserialize)]
+struct ServerQuery {
+    board: String,
+    tray: String,
 }
 
-impl PositionFormat {
-    fn format(&self, pos: &scrabble::Position) -> String {
-        match self {
-            Self::LetterDigit => format!("{:>2}-{:<2}", (b'A' + pos.col as u8) as char, pos.row+1),
-            Self::DigitLetter => format!("{:>2}-{:<2}", pos.col+1, (b'A' + pos.row as u8) as char),
+/// Solves the positions read from the standard input, one `ServerQuery` as JSON per line,
+/// and prints one JSON line per position: the moves (best first) or the error
+fn serve_stdin<D: AsRef<[u8]> + Sync>(
+    context: &scrabble::solver::SolverContext<impl scrabble::LetterScoring, impl scrabble::score_rules::BoardBonus, D>,
+    (rows, cols): (usize, usize),
+    board_format: &scrabble::BoardFormat,
+    strictness: scrabble::Strictness,
+    conf: &Settings,
+) {
+    use std::io::Write;
+    
+    let solve = |line: &str| -> Result<serde_json::Value, String> {
+        let query: ServerQuery = serde_json::from_str(line).map_err(|e| format!("invalid position: {}", e))?;
+        let tray = scrabble::solver::word_finder::TrayRemaining::parse(&query.tray, strictness)
+            .map_err(|e| format!("unknown characters in the tray: {:?}", e))?;
+        let board = scrabble::Board::parse_with_size(&query.board, rows, cols, board_format, strictness)
+            .map_err(|e| format!("invalid board: {:?}", e))?;
+        if strictness == scrabble::Strictness::Strict {
+            scrabble::score_rules::check_scores(&context.rules.score_rules.scoring, &board, &tray)
+                .map_err(|e| format!("tiles without score: {:?}", e))?;
         }
+        
+        let result = context.solve(&tray, &board, &scrabble::solver::RulesOverrides::default());
+        let moves = result.score.iter().rev()
+            .filter(|(mov, _)| !conf.hide_transposed_first_moves || !scrabble::solver::is_transposed_first_move(&board, &mov.as_move()))
+            .take(conf.n_shown.unwrap_or(usize::MAX))
+            .map(|(mov, score)| serde_json::json!({
+                "score": score,
+                "move": mov.as_move().format(conf.position_format).trim(),
+                "words": result.words[mov],
+            }))
+            .collect::<Vec<_>>();
+        Ok(serde_json::json!({ "moves": moves, "truncated": result.truncated }))
+    };
+    
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.expect("reading the standard input");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = solve(&line).unwrap_or_else(|error| serde_json::json!({ "error": error }));
+        let mut out = stdout.lock();
+        writeln!(out, "{}", response).expect("writing the response");
+        out.flush().expect("flushing the response");
     }
 }
 
@@ -452,4 +850,7 @@ impl scrabble::LetterScoring for SimpleLetterScore {
     fn score_for(&self, letter: &scrabble::LetterTile) -> u32 {
         self.map[&tile_to_char(letter)]
     }
+    fn has_score_for(&self, letter: &scrabble::LetterTile) -> bool {
+        self.map.contains_key(&tile_to_char(letter))
+    }
 }
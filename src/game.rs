@@ -0,0 +1,398 @@
+
+//! Games between players choosing their moves with the solver, to test it end-to-end
+
+use super::{
+    Board,
+    Square,
+    LetterTile,
+    Placement,
+    OwnedMove,
+    Rules,
+    LetterScoring,
+    BoardBonus,
+    verify_all_words,
+};
+use super::solver::{SolverContext, RulesOverrides, split_mix};
+use super::solver::score::score_with_words;
+use super::solver::word_finder::TrayRemaining;
+
+/// A turn of a game, `play` is None when the player passed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn {
+    pub player: usize,
+    /// The move and the word it plays
+    pub play: Option<(OwnedMove, String)>,
+    pub score: u32,
+}
+
+/// The state of a game: the board, the trays of the players and the tiles left to draw
+pub struct Game<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]>> {
+    pub solver: SolverContext<Scoring, Bonuses, DictionaryStorage>,
+    pub board: Board,
+    pub racks: Vec<TrayRemaining>,
+    pub scores: Vec<i32>,
+    /// The tiles left in the bag, drawn from the end
+    pub bag: Vec<LetterTile>,
+    /// The player whose turn it is
+    pub current: usize,
+    /// The number of turns passed in a row
+    pub passes: usize,
+    pub history: Vec<Turn>,
+    /// What is needed to take back the last turn, when it is a move
+    undo: Option<Undo>,
+}
+
+struct Undo {
+    /// The tray before the move
+    rack: TrayRemaining,
+    /// The tiles drawn after the move, in the order of the bag
+    drawn: Vec<LetterTile>,
+    passes: usize,
+}
+
+/// The result of `Game::challenge_last`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeOutcome {
+    /// The last turn is not a move (or it was already challenged)
+    NoMove,
+    /// The words of the move are in the dictionary, the move stays
+    Valid,
+    /// The move was taken back because of these words
+    Removed(Vec<(Placement, String)>),
+}
+
+impl<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]> + Sync> Game<Scoring, Bonuses, DictionaryStorage> {
+    /// A game on an empty board, where the tiles of `bag` are shuffled with the `seed`
+    /// and drawn to fill the trays of the `n_players` players (see `ScoreRules::rack_size`)
+    pub fn new(
+        rules: Rules<Scoring, Bonuses, DictionaryStorage>,
+        bag: &TrayRemaining, n_players: usize, seed: u64,
+    ) -> Self {
+        let mut bag = bag.tiles().collect::<Vec<_>>();
+        let mut state = seed;
+        for i in (1..bag.len()).rev() {
+            let j = (split_mix(&mut state) % (i + 1) as u64) as usize;
+            bag.swap(i, j);
+        }
+        
+        let mut game = Game {
+            solver: SolverContext::new(rules),
+            board: Board::empty(),
+            racks: vec![TrayRemaining::new([0; 256], 0).unwrap(); n_players],
+            scores: vec![0; n_players],
+            bag,
+            current: 0,
+            passes: 0,
+            history: vec![],
+            undo: None,
+        };
+        for player in 0..n_players {
+            game.refill(player);
+        }
+        game
+    }
+    
+    /// The tiles drawn after the last turn, in the order of the bag, empty if it was not a move
+    pub fn last_drawn(&self) -> &[LetterTile] {
+        self.undo.as_ref().map_or(&[], |undo| &undo.drawn)
+    }
+    
+    /// Whether a player played all the tiles once the bag is empty, or every player passed in a row
+    pub fn is_over(&self) -> bool {
+        (self.bag.is_empty() && self.racks.iter().any(|rack| rack.tiles().next().is_none()))
+        || self.passes >= self.racks.len()
+    }
+    
+    /// Plays the move for the current player, `word` gives the letters of the wildcards
+    ///
+    /// The words are not checked against the dictionary. Gives the score of the move,
+    /// None if the tiles are not in the tray, a square is not empty, or `word` does
+    /// not fit on the board with the move
+    pub fn play(&mut self, a_move: &OwnedMove, word: &str) -> Option<u32> {
+        let mov = a_move.as_move();
+        if !mov.is_structurally_valid(&self.board) {
+            return None
+        }
+        let rack = self.racks[self.current].after_move(&mov)?;
+        let letters = crate::resolved_tiles(&self.board, &mov, word)?;
+        
+        let score = score_with_words(&self.board, &mov, &[word], &self.solver.rules.score_rules);
+        for ((pos, value), letter) in mov.positions().zip(mov.placed_tiles()).zip(letters) {
+            self.board.letter_table.set(pos, Square::Filled(letter));
+            self.board.value_table.set(pos, Square::Filled(value));
+        }
+        
+        let previous = std::mem::replace(&mut self.racks[self.current], rack);
+        let drawn = self.refill(self.current);
+        self.undo = Some(Undo { rack: previous, drawn, passes: self.passes });
+        self.scores[self.current] += score as i32;
+        self.history.push(Turn { player: self.current, play: Some((a_move.clone(), word.to_owned())), score });
+        self.passes = 0;
+        self.current = (self.current + 1) % self.racks.len();
+        Some(score)
+    }
+    
+    /// The current player passes its turn
+    pub fn pass(&mut self) {
+        self.undo = None;
+        self.history.push(Turn { player: self.current, play: None, score: 0 });
+        self.passes += 1;
+        self.current = (self.current + 1) % self.racks.len();
+    }
+    
+    /// Checks the words of the board formed by the last move against the dictionary
+    ///
+    /// When one of them is not in it, the move is taken back: its tiles go back
+    /// to the tray, the tiles drawn after it go back to the bag, and the turn
+    /// counts as a pass with no score
+    pub fn challenge_last(&mut self) -> ChallengeOutcome {
+        let (undo, turn) = match (self.undo.take(), self.history.last_mut()) {
+            (Some(undo), Some(turn)) => (undo, turn),
+            _ => return ChallengeOutcome::NoMove,
+        };
+        let (a_move, _) = turn.play.as_ref().expect("only a move can be undone");
+        let positions = a_move.as_move().positions().collect::<Vec<_>>();
+        
+        let words = verify_all_words(&self.board, &self.solver.rules.dictionary).err().unwrap_or_default()
+            .into_iter()
+            .filter(|(place, word)| {
+                std::iter::successors(Some(*place), |square| Some(square.next()))
+                    .take(word.len())
+                    .any(|square| positions.contains(&square.0))
+            })
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            self.undo = Some(undo);
+            return ChallengeOutcome::Valid
+        }
+        
+        for &pos in &positions {
+            self.board.letter_table.set(pos, Square::Empty);
+            self.board.value_table.set(pos, Square::Empty);
+        }
+        let player = turn.player;
+        self.scores[player] -= turn.score as i32;
+        turn.play = None;
+        turn.score = 0;
+        
+        self.racks[player] = undo.rack;
+        self.bag.extend(undo.drawn);
+        self.passes = undo.passes + 1;
+        
+        ChallengeOutcome::Removed(words)
+    }
+    
+    /// Draws tiles from the bag until the tray of the player is full, gives the tiles drawn
+    fn refill(&mut self, player: usize) -> Vec<LetterTile> {
+        let rack = &mut self.racks[player];
+        let n_drawn = self.solver.rules.score_rules.rack_size.saturating_sub(rack.tiles().count()).min(self.bag.len());
+        let drawn = self.bag.split_off(self.bag.len() - n_drawn);
+        for &tile in &drawn {
+            let mut letters = [0; 256];
+            let mut n_wildcards = 0;
+            for tile in rack.tiles().chain(std::iter::once(tile)) {
+                match tile {
+                    LetterTile::Letter(l) => letters[l.0 as usize] += 1,
+                    LetterTile::Wildcard => n_wildcards += 1,
+                }
+            }
+            *rack = TrayRemaining::new(letters, n_wildcards).expect("the tiles come from trays");
+        }
+        drawn
+    }
+}
+
+/// A turn of a simulated game, with the tiles in the tray before it and drawn after it
+///
+/// The tiles are written as for `TrayRemaining::parse`, with stars for wildcards
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TranscriptTurn {
+    pub player: usize,
+    pub rack: String,
+    /// The move (as shown by `Move`) and the word it plays, None for a pass
+    pub play: Option<(String, String)>,
+    pub score: u32,
+    pub drawn: String,
+}
+
+/// The turns of a simulated game, in the order they were played
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct GameTranscript {
+    pub turns: Vec<TranscriptTurn>,
+}
+
+/// The end of a simulated game
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    /// The scores after the value of the unplayed tiles is counted
+    pub scores: Vec<i32>,
+    pub history: Vec<Turn>,
+    pub transcript: GameTranscript,
+    pub board: Board,
+}
+
+/// Plays a whole game between two strategies, from the tiles of `bag` shuffled with the `seed`
+///
+/// A strategy gives the move of the current player and its word (like `greedy`),
+/// or None to pass. A move that cannot be played is a pass. At the end, the value
+/// of the unplayed tiles of each tray is subtracted from its player's score, and
+/// the player that played all of them gets the value of the trays of the others
+pub fn simulate_game<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]> + Sync>(
+    rules: Rules<Scoring, Bonuses, DictionaryStorage>,
+    bag: &TrayRemaining,
+    strategy_a: impl Fn(&Game<Scoring, Bonuses, DictionaryStorage>) -> Option<(OwnedMove, String)>,
+    strategy_b: impl Fn(&Game<Scoring, Bonuses, DictionaryStorage>) -> Option<(OwnedMove, String)>,
+    seed: u64,
+) -> GameResult {
+    let mut game = Game::new(rules, bag, 2, seed);
+    let mut transcript = GameTranscript::default();
+    
+    while !game.is_over() {
+        let player = game.current;
+        let rack = game.racks[player].tiles().map(|tile| tile.to_string()).collect();
+        let play = if game.current == 0 { strategy_a(&game) } else { strategy_b(&game) };
+        match play {
+            Some((a_move, word)) => if game.play(&a_move, &word).is_none() {
+                log::warn!("the move {} of player {} cannot be played", a_move, game.current);
+                game.pass();
+            },
+            None => game.pass(),
+        }
+        
+        let turn = game.history.last().expect("a turn was just played");
+        transcript.turns.push(TranscriptTurn {
+            player,
+            rack,
+            play: turn.play.as_ref().map(|(a_move, word)| (a_move.to_string(), word.clone())),
+            score: turn.score,
+            drawn: game.last_drawn().iter().map(LetterTile::to_string).collect(),
+        });
+    }
+    
+    let adjustments = final_adjustments(&game.racks, &game.solver.rules.score_rules.scoring);
+    for (score, adjustment) in game.scores.iter_mut().zip(adjustments) {
+        *score += adjustment;
+    }
+    
+    GameResult {
+        scores: game.scores,
+        history: game.history,
+        transcript,
+        board: game.board,
+    }
+}
+
+/// The points each player gets at the end of the game, from the tiles left in the trays
+///
+/// The value of the unplayed tiles of a tray is subtracted from its player's score,
+/// and the player that played all its tiles (if any) gets the value of all the trays.
+/// Wildcards count as the score given to them by `scoring`, which is 0 usually
+pub fn final_adjustments(racks: &[TrayRemaining], scoring: &impl LetterScoring) -> Vec<i32> {
+    let values = racks.iter()
+        .map(|rack| rack.tiles().map(|tile| scoring.score_for(&tile) as i32).sum::<i32>())
+        .collect::<Vec<_>>();
+    let total = values.iter().sum::<i32>();
+    racks.iter().zip(&values).map(|(rack, value)| {
+        if rack.tiles().next().is_none() {
+            total
+        } else {
+            -value
+        }
+    }).collect()
+}
+
+/// The strategy playing the move with the highest score
+pub fn greedy<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]> + Sync>(
+    game: &Game<Scoring, Bonuses, DictionaryStorage>,
+) -> Option<(OwnedMove, String)> {
+    let mut result = game.solver.solve(&game.racks[game.current], &game.board, &RulesOverrides::default());
+    let (a_move, _) = result.score.pop()?;
+    let word = result.words.remove(&a_move)?.into_iter().next()?;
+    Some((a_move, word))
+}
+
+#[test]
+fn end_of_game_adjustments() {
+    use crate::score_rules::EnglishScrabbleScoring;
+    
+    let rack = |tiles| TrayRemaining::from_compact_str(tiles).unwrap();
+    
+    // the first player went out, and gets the tiles of the second one
+    assert_eq!(final_adjustments(&[rack(""), rack("q1a1*1")], &EnglishScrabbleScoring), vec![11, -11]);
+    
+    // the game ended by passes, nobody went out
+    assert_eq!(final_adjustments(&[rack("z1"), rack("e2")], &EnglishScrabbleScoring), vec![-10, -2]);
+    
+    // a wildcard left is not going out
+    assert_eq!(final_adjustments(&[rack("*1"), rack("")], &EnglishScrabbleScoring), vec![0, 0]);
+}
+
+#[test]
+fn greedy_game() {
+    let rules = || crate::test_utils::rules(&["at", "ta", "tat", "cat", "act", "taco", "coat", "oat", "to"]);
+    let bag = TrayRemaining::from_compact_str("a4c2o2t6*1").unwrap();
+    
+    let result = simulate_game(rules(), &bag, greedy, greedy, 3);
+    assert!(result.history.iter().any(|turn| turn.play.is_some()));
+    
+    // the points of the moves, minus the tiles left
+    let played = |player| result.history.iter()
+        .filter(|turn| turn.player == player)
+        .map(|turn| turn.score as i32)
+        .sum::<i32>();
+    assert!(result.scores[0] <= played(0) + played(1));
+    assert!(result.scores[1] <= played(0) + played(1));
+    
+    // the same seed gives the same game
+    assert_eq!(simulate_game(rules(), &bag, greedy, greedy, 3).history, result.history);
+    
+    // the transcript follows the turns, and a move draws as many tiles as it plays while the bag is not empty
+    assert_eq!(result.transcript.turns.len(), result.history.len());
+    let mut bag_left = bag.tiles().count() - 2 * 7;
+    for (entry, turn) in result.transcript.turns.iter().zip(&result.history) {
+        assert_eq!((entry.player, entry.score), (turn.player, turn.score));
+        let n_placed = turn.play.as_ref().map_or(0, |(a_move, _)| a_move.as_move().tiles_placed());
+        assert_eq!(entry.drawn.len(), n_placed.min(bag_left));
+        bag_left -= entry.drawn.len();
+    }
+    assert!(serde_json::to_string(&result.transcript).unwrap().starts_with(r#"{"turns":[{"player":0,"rack":""#));
+    
+    // every word on the board is in the dictionary
+    let rules = rules();
+    crate::verify_all_words(&result.board, &rules.dictionary).unwrap();
+}
+
+#[test]
+fn challenged_moves() {
+    use crate::{Position, Letter, Direction};
+    
+    let mut rules = crate::test_utils::rules(&["cat"]);
+    rules.score_rules.rack_size = 3;
+    let mut game = Game::new(rules, &TrayRemaining::from_compact_str("a2c2t2e8").unwrap(), 2, 0);
+    game.racks[0] = TrayRemaining::from_compact_str("a1c1t1").unwrap();
+    game.racks[1] = TrayRemaining::from_compact_str("a1c1t1").unwrap();
+    assert_eq!(game.challenge_last(), ChallengeOutcome::NoMove);
+    
+    let horizontal = |col, tiles: &[u8]| OwnedMove::MultiLetters(
+        Placement(Position { row: 7, col }, Direction::Horizontal),
+        LetterTile::Letter(Letter(tiles[0])),
+        tiles[1..].iter().map(|&l| (0, LetterTile::Letter(Letter(l)))).collect(),
+    );
+    
+    game.play(&horizontal(7, b"cat"), "cat").unwrap();
+    assert_eq!(game.challenge_last(), ChallengeOutcome::Valid);
+    assert_eq!(game.challenge_last(), ChallengeOutcome::Valid);
+    
+    let bag = game.bag.clone();
+    game.play(&horizontal(4, b"act"), "actcat").unwrap();
+    assert_eq!(
+        game.challenge_last(),
+        ChallengeOutcome::Removed(vec![(Placement(Position { row: 7, col: 4 }, Direction::Horizontal), "actcat".to_owned())]),
+    );
+    assert_eq!(game.bag, bag);
+    assert_eq!(format!("{:?}", game.racks[1]), "act[3 letters]");
+    assert_eq!(game.scores[1], 0);
+    assert_eq!(game.board.letter_table.get(Position { row: 7, col: 4 }), Some(&Square::Empty));
+    assert_eq!(game.history.last().unwrap().play, None);
+    assert_eq!(game.challenge_last(), ChallengeOutcome::NoMove);
+}
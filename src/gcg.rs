@@ -0,0 +1,306 @@
+
+use super::{
+    Board,
+    Square,
+    LetterTile,
+    Letter,
+    Position,
+    Placement,
+    CoordNotation,
+    OwnedMove,
+    Rules,
+    LetterScoring,
+    BoardBonus,
+    Strictness,
+    move_from_placed,
+    verify_all_words,
+};
+use super::solver::word_finder::TrayRemaining;
+use super::solver::score::naive_score;
+
+/// A move of a GCG transcript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveRecord {
+    pub player: String,
+    pub rack: String,
+    /// None for a pass or an exchange
+    pub play: Option<OwnedMove>,
+    pub score: u32,
+}
+
+/// Why a GCG transcript is not valid, `line` starts at 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GcgError {
+    /// The line cannot be read
+    Syntax { line: usize },
+    /// The line is a valid GCG line that is not handled (challenges, end of game)
+    Unsupported { line: usize },
+    /// The word does not fit with the tiles of the board
+    Mismatch { line: usize },
+    /// The recorded score is not the one computed for the move
+    WrongScore { line: usize, recorded: u32, computed: u32 },
+    /// The move leaves words that are not in the dictionary on the board
+    InvalidWords { line: usize, words: Vec<(Placement, String)> },
+    /// The transcript has fewer moves than the ply asked for
+    NoPly { ply: usize, plies: usize },
+    /// The tiles of the board and of the racks known at the line are not all in the bag of the game
+    UnknownTiles { line: usize },
+}
+
+/// The position of a game before one of the moves of its GCG transcript (see `state_at_ply`)
+#[derive(Debug, Clone)]
+pub struct GameState {
+    pub board: Board,
+    /// The names of the players, in the order of their first move
+    pub players: Vec<String>,
+    /// The tray of each player, None when no later move of the transcript tells it
+    pub racks: Vec<Option<TrayRemaining>>,
+    pub scores: Vec<u32>,
+    /// The tiles of the game that are neither on the board nor in the known racks
+    pub bag: TrayRemaining,
+    /// The player whose turn it is
+    pub current: usize,
+}
+
+/// Replays the moves of a GCG transcript from an empty board, and checks their score and words
+///
+/// In a move, `.` is a tile already on the board and lowercase letters are wildcards.
+/// The words are checked with the `dictionary` of the `rules`
+pub fn verify_gcg(
+    gcg: &str,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+) -> Result<Vec<MoveRecord>, GcgError> {
+    replay_with(gcg, rules, |_, _| {})
+}
+
+/// The board, racks, scores and bag of a game before the move `ply` of its GCG transcript,
+/// where `bag` has all the tiles of the game (like `Preset::bag`)
+///
+/// The ply 0 is the empty board, and the number of moves of the transcript is its end.
+/// The whole transcript is checked as by `verify_gcg`. The tray of a player is the rack
+/// recorded at its next move, as no tile is drawn until then
+pub fn state_at_ply(
+    gcg: &str,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+    bag: &TrayRemaining,
+    ply: usize,
+) -> Result<GameState, GcgError> {
+    let mut lines = vec![];
+    let mut board = (ply == 0).then(Board::empty);
+    let records = replay_with(gcg, rules, |line, board_after| {
+        lines.push(line);
+        if lines.len() == ply {
+            board = Some(board_after.clone());
+        }
+    })?;
+    let board = board.ok_or(GcgError::NoPly { ply, plies: records.len() })?;
+    
+    let mut players: Vec<String> = vec![];
+    for record in &records {
+        if !players.contains(&record.player) {
+            players.push(record.player.clone());
+        }
+    }
+    let index = |name: &str| players.iter().position(|player| player == name).unwrap();
+    
+    let mut scores = vec![0; players.len()];
+    for record in &records[..ply] {
+        scores[index(&record.player)] += record.score;
+    }
+    
+    let mut racks = vec![None; players.len()];
+    for (&line, record) in lines.iter().zip(&records).skip(ply) {
+        let rack = &mut racks[index(&record.player)];
+        if rack.is_none() {
+            let tray = TrayRemaining::parse(&record.rack.replace('?', "*"), Strictness::Strict)
+                .map_err(|_| GcgError::Syntax { line })?;
+            *rack = Some(tray);
+        }
+    }
+    
+    let current = match (records.get(ply), records.last()) {
+        (Some(record), _) => index(&record.player),
+        (None, Some(last)) => (index(&last.player) + 1) % players.len(),
+        (None, None) => 0,
+    };
+    
+    // the line of the last move read, for the errors
+    let line = lines.get(ply).or(lines.last()).copied().unwrap_or(0);
+    let board_tiles = board.value_table.iter_positions().filter_map(|(_, square)| square.tile().copied());
+    let rack_tiles = racks.iter().flatten().flat_map(TrayRemaining::tiles);
+    let bag = board_tiles.chain(rack_tiles).try_fold(*bag, |bag, tile| match tile {
+        LetterTile::Letter(Letter(l)) => bag.remove(l),
+        LetterTile::Wildcard => bag.remove_wildcard(),
+    }).ok_or(GcgError::UnknownTiles { line })?;
+    
+    Ok(GameState {
+        board,
+        players,
+        racks,
+        scores,
+        bag,
+        current,
+    })
+}
+
+/// Same as `verify_gcg`, where `after_record` is called with the line of each move
+/// and the board once it is played
+fn replay_with(
+    gcg: &str,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+    mut after_record: impl FnMut(usize, &Board),
+) -> Result<Vec<MoveRecord>, GcgError> {
+    let mut board = Board::empty();
+    let mut records = vec![];
+    
+    for (line, text) in gcg.lines().enumerate() {
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue
+        }
+        let text = text.strip_prefix('>').ok_or(GcgError::Syntax { line })?;
+        let (player, text) = text.split_once(':').ok_or(GcgError::Syntax { line })?;
+        let fields = text.split_whitespace().collect::<Vec<_>>();
+        
+        let (rack, play, score) = match fields[..] {
+            [rack, coordinate, word, score, _total] if score.starts_with('+') || score.starts_with('-') => {
+                (rack, Some((coordinate, word)), score)
+            },
+            // pass or exchange
+            [rack, exchange, score, _total] if exchange.starts_with('-') && exchange != "--" => {
+                (rack, None, score)
+            },
+            [..] if fields.iter().any(|f| *f == "--" || f.starts_with('(')) => return Err(GcgError::Unsupported { line }),
+            _ => return Err(GcgError::Syntax { line }),
+        };
+        let recorded = score.strip_prefix('+').and_then(|s| s.parse().ok()).ok_or(GcgError::Syntax { line })?;
+        
+        let play = match play {
+            None => {
+                if recorded != 0 {
+                    return Err(GcgError::WrongScore { line, recorded, computed: 0 })
+                }
+                None
+            },
+            Some((coordinate, word)) => {
+                let placed = read_play(&board, coordinate, word).ok_or(GcgError::Mismatch { line })?;
+                
+                let arena = typed_arena::Arena::new();
+                let tiles = placed.iter().map(|&(pos, _, value)| (pos, value)).collect::<Vec<_>>();
+                let a_move = move_from_placed(&board, &tiles, &arena).ok_or(GcgError::Mismatch { line })?;
+                
+                let computed = naive_score(&board.value_table, &a_move, &rules.score_rules);
+                if computed != recorded {
+                    return Err(GcgError::WrongScore { line, recorded, computed })
+                }
+                
+                for &(pos, letter, value) in &placed {
+                    board.letter_table.set(pos, Square::Filled(letter));
+                    board.value_table.set(pos, Square::Filled(value));
+                }
+                verify_all_words(&board, &rules.dictionary).map_err(|words| GcgError::InvalidWords { line, words })?;
+                
+                Some(OwnedMove::from(&a_move))
+            },
+        };
+        
+        records.push(MoveRecord {
+            player: player.trim().to_owned(),
+            rack: rack.to_owned(),
+            play,
+            score: recorded,
+        });
+        after_record(line, &board);
+    }
+    
+    Ok(records)
+}
+
+/// The tiles placed by a move (with the letter and the value of each tile),
+/// None if it does not fit on the board
+fn read_play(board: &Board, coordinate: &str, word: &str) -> Option<Vec<(Position, LetterTile, LetterTile)>> {
+    // `H8` is vertical from column H row 8, and `8H` horizontal from row 8 column H
+    let start = CoordNotation::parse(coordinate)?;
+    
+    let mut placed = vec![];
+    let mut place = Some(start);
+    for byte in word.bytes() {
+        let pos = place?.0;
+        let square = *board.letter_table.get(pos)?;
+        if byte == b'.' {
+            square.tile()?;
+        } else if byte.is_ascii_alphabetic() && square == Square::Empty {
+            let letter = LetterTile::Letter(Letter(byte.to_ascii_lowercase()));
+            let value = if byte.is_ascii_lowercase() { LetterTile::Wildcard } else { letter };
+            placed.push((pos, letter, value));
+        } else {
+            return None
+        }
+        place = place?.try_next(board.letter_table.len_in(start.1));
+    }
+    
+    Some(placed)
+}
+
+#[test]
+fn replay() {
+    use crate::Direction;
+    
+    let rules = crate::test_utils::rules(&["cat", "ate", "at"]);
+    
+    let gcg = "#player1 A Alice\n#player2 B Bob\n>A: ACTXYZQ 8G CAT +12 12\n>B: AEIOUXR I7 A.E +5 5\n>A: XYZQ -XYZ +0 12\n";
+    let records = verify_gcg(gcg, &rules).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].play, Some(OwnedMove::MultiLetters(
+        Placement(Position { row: 6, col: 8 }, Direction::Vertical),
+        LetterTile::Letter(Letter(b'a')),
+        vec![(1, LetterTile::Letter(Letter(b'e')))],
+    )));
+    assert_eq!(records[2].play, None);
+    
+    // the wildcard is worth nothing
+    assert_eq!(
+        verify_gcg(">A: ACTXYZQ 8G cAT +12 12", &rules),
+        Err(GcgError::WrongScore { line: 0, recorded: 12, computed: 4 }),
+    );
+    assert_eq!(
+        verify_gcg(">A: ACTXYZQ 8G TAC +12 12", &rules),
+        Err(GcgError::InvalidWords { line: 0, words: vec![(Placement(Position { row: 7, col: 6 }, Direction::Horizontal), "tac".to_owned())] }),
+    );
+}
+
+#[test]
+fn state_of_a_ply() {
+    let rules = crate::test_utils::rules(&["cat", "ate"]);
+    let bag = crate::presets::scrabble_english().bag;
+    
+    let gcg = ">A: ACTEFGH 8G CAT +12 12\n>B: AEIOUDR I7 A.E +5 5\n>A: EFGHIJK -FGH +0 12\n";
+    
+    let start = state_at_ply(gcg, &rules, &bag, 0).unwrap();
+    assert!(start.board.is_empty());
+    assert_eq!(start.bag.tiles().count(), 100 - 7 - 7);
+    
+    // the tray of A is the one of its next move, once it drew after "cat"
+    let state = state_at_ply(gcg, &rules, &bag, 1).unwrap();
+    assert_eq!(state.players, ["A", "B"]);
+    assert_eq!(state.current, 1);
+    assert_eq!(state.scores, [12, 0]);
+    assert_eq!(format!("{:?}", state.racks[0].unwrap()), "efghijk[7 letters]");
+    assert_eq!(format!("{:?}", state.racks[1].unwrap()), "adeioru[7 letters]");
+    assert_eq!(state.board.filled_positions().len(), 3);
+    assert_eq!(state.bag.tiles().count(), 100 - 3 - 7 - 7);
+    assert_eq!(state.bag.count(b'a'), 9 - 1 - 1);
+    
+    // after the exchange, the racks are not known anymore
+    let end = state_at_ply(gcg, &rules, &bag, 3).unwrap();
+    assert_eq!(end.current, 1);
+    assert_eq!(end.scores, [12, 5]);
+    assert!(end.racks.iter().all(Option::is_none));
+    assert_eq!(end.bag.tiles().count(), 100 - 5);
+    
+    assert!(matches!(state_at_ply(gcg, &rules, &bag, 4), Err(GcgError::NoPly { ply: 4, plies: 3 })));
+    assert!(matches!(
+        state_at_ply(">A: QQ 8G CAT +12 12\n>B: QQ I7 A.E +5 5", &rules, &bag, 0),
+        Err(GcgError::UnknownTiles { line: 0 }),
+    ));
+}
@@ -1,11 +1,20 @@
 
 pub mod solver;
 pub mod score_rules;
+pub mod gcg;
+pub mod presets;
+pub mod game;
+#[cfg(test)]
+mod test_utils;
 
 use std::fmt;
+use std::collections::HashSet;
 
 pub const BOARD_SIZE: usize = 15;
 
+/// The version of the format of `Board::to_bytes` and `TrayRemaining::to_bytes`
+pub const BYTES_VERSION: u8 = 1;
+
 pub use score_rules::{LetterScoring, BoardBonus, Bonus};
 use score_rules::ScoreRules;
 
@@ -20,14 +29,160 @@ pub struct Rules<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage:
     /// the board are always interpreted as signifying anything
     pub wildcards_have_multi_meaning: bool,
     
+    /// The minimum length of the word played in the direction of the move
+    ///
+    /// The words formed in the perpendicular direction are not concerned
+    pub min_main_word_len: usize,
+    
+    /// Letters from the tray that each move must play, repeated to require several of them
+    ///
+    /// Wildcards used as these letters do not count
+    pub must_play: Vec<Letter>,
+    
+    /// Whether only the moves playing all the tiles of the tray (aka bingos, see `ScoreRules::rack_size`) are kept
+    pub bingos_only: bool,
+    
+    /// The most tiles of the tray a move can play, no limit if not present
+    pub max_tiles_placed: Option<usize>,
+    
+    /// The moves scoring less are dropped from the scores of the result
+    pub min_score: u32,
+    
     /// The words that can be played
     ///
     /// Words already on the board are not checked
     pub dictionary: fst::Set<DictionaryStorage>,
+    
+    /// The words that can be formed in the perpendicular direction of the move,
+    /// `dictionary` is used if not present
+    pub cross_dictionary: Option<fst::Set<DictionaryStorage>>,
+    
+    /// Words of the dictionaries that cannot be played, in lowercase
+    ///
+    /// They are neither played in the direction of the move nor formed in the perpendicular one
+    pub exclude: HashSet<String>,
+    
+    /// Whether the moves must touch a tile of the board (or cover the center on an empty board)
+    ///
+    /// Puzzles placing words anywhere set it to false, which also frees the first move from the center
+    pub require_attachment: bool,
+    
+    /// The top left and the bottom right squares (included) of the part of the
+    /// board the moves must be played in, the whole board if not present
+    ///
+    /// The word played in the direction of the move must fit in it, the words
+    /// formed in the perpendicular direction can go out
+    pub region: Option<(Position, Position)>,
+    
+    /// Whether the move is the first of the game, which must cover the center,
+    /// guessed from the board if not present (the first move is on an empty board)
+    ///
+    /// A position set up without any tile in the middle of a game is not a first move,
+    /// its moves cannot be attached to any tile. On a board with tiles, a first move
+    /// can also be attached by covering the center
+    pub first_move: Option<bool>,
+}
+
+impl<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]>> Rules<Scoring, Bonuses, DictionaryStorage> {
+    /// The usual rules of a game with this dictionary and these scores, where
+    /// the other fields do not restrict the moves
+    ///
+    /// The fields can then be changed, or given with `..Rules::new(dictionary, score_rules)`
+    pub fn new(dictionary: fst::Set<DictionaryStorage>, score_rules: ScoreRules<Scoring, Bonuses>) -> Self {
+        Rules {
+            score_rules,
+            wildcards_have_multi_meaning: false,
+            min_main_word_len: 0,
+            must_play: vec![],
+            bingos_only: false,
+            max_tiles_placed: None,
+            min_score: 0,
+            dictionary,
+            cross_dictionary: None,
+            exclude: HashSet::new(),
+            require_attachment: true,
+            region: None,
+            first_move: None,
+        }
+    }
+    
+    /// The same rules, where the bonuses of the board are replaced by `f` of them
+    pub fn map_bonuses<Other: BoardBonus>(self, f: impl FnOnce(Bonuses) -> Other) -> Rules<Scoring, Other, DictionaryStorage> {
+        Rules {
+            score_rules: self.score_rules.map_bonuses(f),
+            wildcards_have_multi_meaning: self.wildcards_have_multi_meaning,
+            min_main_word_len: self.min_main_word_len,
+            must_play: self.must_play,
+            bingos_only: self.bingos_only,
+            max_tiles_placed: self.max_tiles_placed,
+            min_score: self.min_score,
+            dictionary: self.dictionary,
+            cross_dictionary: self.cross_dictionary,
+            exclude: self.exclude,
+            require_attachment: self.require_attachment,
+            region: self.region,
+            first_move: self.first_move,
+        }
+    }
+    
+    /// The words that can be formed in the perpendicular direction of the move
+    pub fn cross_dictionary(&self) -> &fst::Set<DictionaryStorage> {
+        self.cross_dictionary.as_ref().unwrap_or(&self.dictionary)
+    }
+    
+    /// The number of words in the dictionary
+    pub fn dictionary_len(&self) -> usize {
+        self.dictionary.len()
+    }
+}
+
+/// Builds a dictionary from a list of words
+///
+/// The words are trimmed and lowercased, empty ones are ignored.
+/// Fails on the first word that contains something else than ascii letters
+pub fn build_dictionary<S: AsRef<str>>(words: impl IntoIterator<Item=S>) -> Result<fst::Set<Vec<u8>>, InvalidWord> {
+    let mut words = words.into_iter()
+        .map(|w| w.as_ref().trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>();
+    
+    if let Some(invalid) = words.iter().find(|w| !w.bytes().all(|b| Alphabet::english().contains(Letter(b)))) {
+        return Err(InvalidWord(invalid.clone()))
+    }
+    
+    words.sort_unstable();
+    words.dedup();
+    
+    let mut build = fst::SetBuilder::memory();
+    build.extend_iter(words).expect("sorted words");
+    Ok(build.into_set())
+}
+
+/// A word that contains something else than ascii letters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidWord(pub String);
+
+/// The words that are in at least one of the dictionaries, to play with several word lists at once
+pub fn union_dictionaries<'a, D: AsRef<[u8]> + 'a>(dictionaries: impl IntoIterator<Item=&'a fst::Set<D>>) -> fst::Set<Vec<u8>> {
+    let mut op = fst::set::OpBuilder::new();
+    for dictionary in dictionaries {
+        op.push(dictionary);
+    }
+    let mut build = fst::SetBuilder::memory();
+    build.extend_stream(op.union()).expect("the union is sorted");
+    build.into_set()
+}
+
+/// The names of the dictionaries that contain the word, in the order of `dictionaries`
+pub fn word_sources<'a, D: AsRef<[u8]>>(word: &str, dictionaries: &'a [(String, fst::Set<D>)]) -> Vec<&'a str> {
+    dictionaries.iter()
+        .filter(|(_, dictionary)| dictionary.contains(word))
+        .map(|(name, _)| name.as_str())
+        .collect()
 }
 
 // we restrict to use u8 as letters, and u8 to represent the number of identical letters in a tray
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Letter(pub u8);
 
 impl fmt::Display for Letter {
@@ -41,6 +196,43 @@ impl fmt::Debug for Letter {
     }
 }
 
+/// The letters used by a language, shown in the order of their byte
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: solver::letter_set::LetterSet,
+}
+
+impl Alphabet {
+    pub fn new(letters: impl IntoIterator<Item=Letter>) -> Self {
+        Self { letters: letters.into_iter().collect() }
+    }
+    
+    /// The letters from `a` to `z`
+    pub const fn english() -> Self {
+        Self { letters: solver::letter_set::LetterSet::ALPHABET }
+    }
+    
+    pub fn contains(&self, letter: Letter) -> bool {
+        self.letters.contains(letter)
+    }
+    
+    /// The position of the letter in `letters`, None if it is not in the alphabet
+    pub fn index(&self, letter: Letter) -> Option<usize> {
+        self.letters.index_of(letter)
+    }
+    
+    /// All the letters, in order
+    pub fn letters(&self) -> impl Iterator<Item=Letter> + '_ {
+        (0..=255u8).map(Letter).filter(move |&l| self.contains(l))
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Square {
     Empty,
@@ -62,13 +254,22 @@ impl Square {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum LetterTile {
     Wildcard,
     Letter(Letter),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+impl fmt::Display for LetterTile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LetterTile::Letter(l) => write!(f, "{}", l),
+            LetterTile::Wildcard => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Direction {
     Vertical,
     Horizontal,
@@ -83,7 +284,7 @@ impl Direction {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Position {
     pub row: usize,
     pub col: usize,
@@ -110,7 +311,7 @@ impl std::ops::IndexMut<Direction> for Position {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Placement(pub Position, pub Direction);
 
 impl Placement {
@@ -124,6 +325,26 @@ impl Placement {
         self
     }
     
+    /// The next placement, None if it is out of a board of `size` squares per line
+    pub fn try_next(mut self, size: usize) -> Option<Self> {
+        if self.0[self.1] + 1 < size {
+            self.0[self.1] += 1;
+            Some(self)
+        } else {
+            None
+        }
+    }
+    
+    /// The previous placement, None if it is out of the board
+    pub fn try_back(mut self, size: usize) -> Option<Self> {
+        if self.0[self.1] > 0 && self.0[self.1] <= size {
+            self.0[self.1] -= 1;
+            Some(self)
+        } else {
+            None
+        }
+    }
+    
     /// A placement at the same position, but different direction
     pub fn perp(self) -> Self {
         Self(self.0, self.1.perp())
@@ -154,7 +375,7 @@ impl Placement {
         let mut start = first;
         start[dir] = start[dir].min(second[dir]);
         
-        while let Some(new) = iter.next() {
+        for new in iter {
             if new[dir.perp()] != start[dir.perp()] {
                 return None
             } else {
@@ -166,12 +387,195 @@ impl Placement {
 }
 
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A move of tiles from the tray to the board
+///
+/// Moves are ordered by kind (`SingleLetter` first), then by position (row, then
+/// column), direction (vertical first), and tiles (wildcards first, then by letter,
+/// the skipped squares before the tile for the other tiles)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Move<'a> {
     SingleLetter(Position, LetterTile),
     MultiLetters(Placement, LetterTile, &'a [(usize, LetterTile)]), // usize is the number of skipped squares
 }
 
+impl<'a> Move<'a> {
+    /// The position of the first tile placed by the move
+    pub fn first_position(&self) -> Position {
+        match self {
+            Move::SingleLetter(pos, _) => *pos,
+            Move::MultiLetters(place, _, _) => place.0,
+        }
+    }
+    
+    /// The same move in the form given by the solver, where a single tile is always a `SingleLetter`
+    pub fn canonical(&self) -> Move<'a> {
+        match self {
+            Move::MultiLetters(place, tile, []) => Move::SingleLetter(place.0, *tile),
+            _ => self.clone(),
+        }
+    }
+    
+    /// The number of tiles from the tray placed on the board by the move
+    pub fn tiles_placed(&self) -> usize {
+        match self {
+            Move::SingleLetter(_, _) => 1,
+            Move::MultiLetters(_, _, others) => 1 + others.len(),
+        }
+    }
+    
+    /// The tiles from the tray placed on the board by the move, in order
+    pub fn placed_tiles(&self) -> impl Iterator<Item=LetterTile> + '_ {
+        let (first, others): (_, &[(usize, LetterTile)]) = match self {
+            Move::SingleLetter(_, tile) => (*tile, &[]),
+            Move::MultiLetters(_, first, others) => (*first, others),
+        };
+        std::iter::once(first).chain(others.iter().map(|(_, tile)| *tile))
+    }
+    
+    /// The positions of the tiles placed by the move, in order
+    pub fn positions(&self) -> impl Iterator<Item=Position> + '_ {
+        let (first, others): (_, &[(usize, LetterTile)]) = match self {
+            Move::SingleLetter(pos, _) => (Placement(*pos, Direction::Horizontal), &[]),
+            Move::MultiLetters(place, _, others) => (*place, others),
+        };
+        std::iter::once(first.0).chain(others.iter().scan(first, |place, (step, _)| {
+            place.0[place.1] += step + 1;
+            Some(place.0)
+        }))
+    }
+    
+    /// The squares of the words formed by the move (before it is played on the
+    /// `board`), including the tiles of the board they go through
+    ///
+    /// These are the squares of the word played in the direction of the move, then
+    /// if `with_cross_words` the ones of the words formed in the perpendicular
+    /// direction. A single tile has no direction, so both its words are given
+    pub fn footprint(&self, board: &Board, with_cross_words: bool) -> Vec<Position> {
+        let placed = self.positions().collect::<Vec<_>>();
+        let is_filled = |pos| placed.contains(&pos) || board.letter_table.get(pos).and_then(Square::tile).is_some();
+        let word_at = |pos, dir| {
+            let size = board.letter_table.len_in(dir);
+            let mut start = Placement(pos, dir);
+            while let Some(back) = start.try_back(size).filter(|back| is_filled(back.0)) {
+                start = back;
+            }
+            let word = std::iter::successors(Some(start), |place| place.try_next(size).filter(|next| is_filled(next.0)))
+                .map(|place| place.0)
+                .collect::<Vec<_>>();
+            if word.len() > 1 { word } else { vec![] }
+        };
+        
+        let mut footprint = match self {
+            Move::SingleLetter(pos, _) => {
+                let mut words = word_at(*pos, Direction::Horizontal);
+                words.extend(word_at(*pos, Direction::Vertical).into_iter().filter(|&p| p != *pos));
+                return words
+            },
+            Move::MultiLetters(place, _, _) => word_at(place.0, place.1),
+        };
+        if with_cross_words {
+            if let Move::MultiLetters(place, _, _) = self {
+                for &pos in &placed {
+                    footprint.extend(word_at(pos, place.1.perp()).into_iter().filter(|&p| p != pos));
+                }
+            }
+        }
+        footprint
+    }
+    
+    /// The position, the direction and the tiles of the move, the squares skipped
+    /// over tiles of the board being underscores (for example ` H-8  →, c_t`)
+    pub fn format(&self, position_format: PositionFormat) -> String {
+        match self {
+            Move::SingleLetter(pos, tile) => {
+                format!("{},   {}", position_format.format(pos), tile)
+            },
+            Move::MultiLetters(place, first, others) => {
+                format!(
+                    "{} {}, {}",
+                    position_format.format(&place.0),
+                    match place.1 {
+                        Direction::Horizontal => "→",
+                        Direction::Vertical => "↓",
+                    },
+                    std::iter::once(first.to_string()).chain(
+                        others.iter().map(|(n, tile)| "_".repeat(*n) + &tile.to_string())
+                    ).collect::<String>(),
+                )
+            },
+        }
+    }
+    
+    /// Whether the tiles of the move are placed on empty squares of the board,
+    /// and the skipped squares are the filled ones between them
+    ///
+    /// The words are not checked, this only avoids panics when scoring a move built elsewhere
+    pub fn is_structurally_valid(&self, board: &Board) -> bool {
+        let is_empty = |pos| board.letter_table.get(pos) == Some(&Square::Empty);
+        match self {
+            Move::SingleLetter(pos, _) => is_empty(*pos),
+            Move::MultiLetters(place, _, others) => {
+                if !is_empty(place.0) {
+                    return false
+                }
+                let size = board.letter_table.len_in(place.1);
+                let mut place = *place;
+                for &(step, _) in others.iter() {
+                    for _ in 0..step {
+                        match place.try_next(size) {
+                            Some(next) if !is_empty(next.0) => place = next,
+                            _ => return false,
+                        }
+                    }
+                    match place.try_next(size) {
+                        Some(next) if is_empty(next.0) => place = next,
+                        _ => return false,
+                    }
+                }
+                true
+            },
+        }
+    }
+}
+
+/// Same as `Move::format` with the default `PositionFormat`, without the padding
+impl fmt::Display for Move<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(PositionFormat::default()).trim())
+    }
+}
+
+/// A `Move` owning its tiles, that can be built without an arena, with the same order as `Move`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OwnedMove {
+    SingleLetter(Position, LetterTile),
+    MultiLetters(Placement, LetterTile, Vec<(usize, LetterTile)>), // usize is the number of skipped squares
+}
+
+impl OwnedMove {
+    pub fn as_move(&self) -> Move<'_> {
+        match self {
+            OwnedMove::SingleLetter(pos, tile) => Move::SingleLetter(*pos, *tile),
+            OwnedMove::MultiLetters(place, first, others) => Move::MultiLetters(*place, *first, &others[..]),
+        }
+    }
+}
+
+impl fmt::Display for OwnedMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_move().fmt(f)
+    }
+}
+
+impl<'a> From<&Move<'a>> for OwnedMove {
+    fn from(mov: &Move<'a>) -> Self {
+        match mov {
+            Move::SingleLetter(pos, tile) => OwnedMove::SingleLetter(*pos, *tile),
+            Move::MultiLetters(place, first, others) => OwnedMove::MultiLetters(*place, *first, others.to_vec()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Board {
     pub letter_table: Table<Square>,
@@ -185,6 +589,511 @@ impl Board {
             value_table: Table::fill_with(Square::Empty),
         }
     }
+    
+    /// An empty board of `rows` rows of `cols` squares
+    ///
+    /// The solver handles such boards, but the formats (like `parse` or `to_bytes`)
+    /// and the bonuses of `ScrabbleBonus` are only for the usual size
+    pub fn with_size(rows: usize, cols: usize) -> Self {
+        Self {
+            letter_table: Table::with_size(rows, cols, Square::Empty),
+            value_table: Table::with_size(rows, cols, Square::Empty),
+        }
+    }
+    
+    /// Whether no tile is on the board, which means the next move is the opening one
+    pub fn is_empty(&self) -> bool {
+        self.letter_table.iter_positions().all(|(_, square)| square == &Square::Empty)
+    }
+    
+    /// The positions of the squares with a tile, row by row
+    pub fn filled_positions(&self) -> Vec<Position> {
+        self.letter_table.iter_positions()
+        .filter(|(_, square)| matches!(square, Square::Filled(_)))
+        .map(|(pos, _)| pos)
+        .collect()
+    }
+    
+    /// Reads a board where one line corresponds to one row of the board
+    ///
+    /// Letters are tiles (uppercase ones being wildcards used as the letter),
+    /// stars are wildcards, and the empty squares are given by the `format`
+    pub fn parse(board: &str, format: &BoardFormat, strictness: Strictness) -> Result<Board, BoardParseError> {
+        Self::parse_with_size(board, BOARD_SIZE, BOARD_SIZE, format, strictness)
+    }
+    
+    /// Same as `parse`, for a board of `rows` rows of `cols` squares
+    ///
+    /// Empty squares after the end of the board are ignored, but a tile there is an error
+    pub fn parse_with_size(
+        board: &str, rows: usize, cols: usize,
+        format: &BoardFormat, strictness: Strictness,
+    ) -> Result<Board, BoardParseError> {
+        let mut errors = vec![];
+        let mut out_of_board = None;
+        let mut parsed = Board::with_size(rows, cols);
+        board.lines().enumerate().for_each(|(i, line)| {
+            line.bytes().enumerate().for_each(|(j, byte)| {
+                let (
+                    letter_tile,
+                    value_tile,
+                ) = if byte.is_ascii_alphabetic() {
+                    let t = LetterTile::Letter(Letter(byte.to_ascii_lowercase()));
+                    (t, if byte.is_ascii_uppercase() {LetterTile::Wildcard} else {t})
+                } else if byte == b'*' {
+                    (LetterTile::Wildcard, LetterTile::Wildcard)
+                } else if format.empty_chars.contains(&byte) {
+                    return // leave empty
+                } else {
+                    match strictness {
+                        Strictness::Lenient => log::warn!("a byte in the given board is neither a letter, a wildcard (*), or empty: {}", byte),
+                        Strictness::Strict => errors.push(UnknownCharacter { byte, line: i, column: j }),
+                    }
+                    return
+                };
+                let pos = Position { row: i, col: j };
+                if i >= rows || j >= cols {
+                    out_of_board.get_or_insert(pos);
+                    return
+                }
+                parsed.letter_table.set(pos, Square::Filled(letter_tile));
+                parsed.value_table.set(pos, Square::Filled(value_tile));
+            })
+        });
+        if let Some(pos) = out_of_board {
+            Err(BoardParseError::OutOfBoard(pos))
+        } else if errors.is_empty() {
+            Ok(parsed)
+        } else {
+            Err(BoardParseError::UnknownCharacters(errors))
+        }
+    }
+    
+    const BYTES_MAGIC: u8 = b'B';
+    
+    /// A compact encoding of the board, that can be read with `from_bytes`
+    ///
+    /// After a magic byte and the version, a byte per square row by row: 0 when empty,
+    /// 1 to 26 for the letters, with the high bit for wildcards. None if the board
+    /// contains other letters
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let mut bytes = vec![Self::BYTES_MAGIC, BYTES_VERSION];
+        for (pos, letter) in self.letter_table.iter_positions() {
+            let value = self.value_table.get(pos)?;
+            bytes.push(match (letter, value) {
+                (Square::Empty, Square::Empty) => 0,
+                (Square::Filled(LetterTile::Wildcard), Square::Filled(LetterTile::Wildcard)) => 0x80,
+                (Square::Filled(LetterTile::Letter(l)), value) if Alphabet::english().contains(*l) => {
+                    let wildcard = match value {
+                        Square::Filled(LetterTile::Wildcard) => 0x80,
+                        Square::Filled(LetterTile::Letter(v)) if v == l => 0,
+                        _ => return None,
+                    };
+                    wildcard | (l.0 - b'a' + 1)
+                },
+                _ => return None,
+            });
+        }
+        Some(bytes)
+    }
+    
+    /// Reads a board encoded with `to_bytes`, None if the bytes are not valid
+    pub fn from_bytes(bytes: &[u8]) -> Option<Board> {
+        match bytes {
+            [Self::BYTES_MAGIC, BYTES_VERSION, squares @ ..] if squares.len() == BOARD_SIZE * BOARD_SIZE => {
+                let mut board = Board::empty();
+                for (i, &byte) in squares.iter().enumerate() {
+                    let pos = Position { row: i / BOARD_SIZE, col: i % BOARD_SIZE };
+                    let (letter, value) = match (byte & 0x80 != 0, byte & 0x7f) {
+                        (false, 0) => continue,
+                        (true, 0) => (LetterTile::Wildcard, LetterTile::Wildcard),
+                        (wildcard, l @ 1..=26) => {
+                            let letter = LetterTile::Letter(Letter(b'a' + l - 1));
+                            (letter, if wildcard { LetterTile::Wildcard } else { letter })
+                        },
+                        _ => return None,
+                    };
+                    board.letter_table.set(pos, Square::Filled(letter));
+                    board.value_table.set(pos, Square::Filled(value));
+                }
+                Some(board)
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The squares that are different on the two boards, with the square before and after
+pub fn board_diff(before: &Board, after: &Board) -> Vec<(Position, Square, Square)> {
+    before.letter_table.iter_positions()
+    .filter_map(|(pos, &old)| {
+        let new = *after.letter_table.get(pos)?;
+        if old != new {
+            Some((pos, old, new))
+        } else {
+            None
+        }
+    })
+    .collect()
+}
+
+/// The move that turns the board `before` into `after`
+///
+/// None if a tile is removed or changed, if no tile is added, or if the added
+/// tiles are not on a single line of tiles
+pub fn move_from_diff(before: &Board, after: &Board) -> Option<OwnedMove> {
+    let mut placed = vec![];
+    for (pos, old, _) in board_diff(before, after) {
+        if old != Square::Empty {
+            return None
+        }
+        placed.push((pos, *after.value_table.get(pos)?.tile()?));
+    }
+    
+    let arena = typed_arena::Arena::new();
+    move_from_placed(before, &placed, &arena).as_ref().map(OwnedMove::from)
+}
+
+/// The move that places the tiles at the given positions of the board
+///
+/// None if a position is not an empty square, if no tile is placed, or if the
+/// tiles are not on a single line of tiles
+pub fn move_from_placed<'a>(
+    board: &Board, placed: &[(Position, LetterTile)],
+    arena: &'a typed_arena::Arena<(usize, LetterTile)>,
+) -> Option<Move<'a>> {
+    for (pos, _) in placed {
+        if *board.letter_table.get(*pos)? != Square::Empty {
+            return None
+        }
+    }
+    
+    let dir = match Placement::find_alignment(placed.iter().map(|&(pos, _)| pos))? {
+        Err(pos) => return Some(Move::SingleLetter(pos, placed[0].1)),
+        Ok(place) => place.1,
+    };
+    let mut placed = placed.to_vec();
+    placed.sort_unstable_by_key(|(pos, _)| pos[dir]);
+    
+    let mut others = vec![];
+    for pair in placed.windows(2) {
+        let (previous, (next, tile)) = (pair[0].0, pair[1]);
+        // the skipped squares must have a tile
+        let skipped = next[dir] - previous[dir] - 1;
+        let mut square = Placement(previous, dir);
+        for _ in 0..skipped {
+            square = square.next();
+            board.letter_table.get(square.0)?.tile()?;
+        }
+        others.push((skipped, tile));
+    }
+    
+    Some(Move::MultiLetters(Placement(placed[0].0, dir), placed[0].1, arena.alloc_extend(others)))
+}
+
+/// Checks that every word of 2 letters or more on the board is in the dictionary
+///
+/// Gives the placement and the text of the words that are not, where wildcards
+/// (`*`) accept any letter
+pub fn verify_all_words(board: &Board, dictionary: &fst::Set<impl AsRef<[u8]>>) -> Result<(), Vec<(Placement, String)>> {
+    let mut invalid = vec![];
+    
+    for &dir in &[Direction::Horizontal, Direction::Vertical] {
+        for i in 0..board.letter_table.len_in(dir.perp()) {
+            let mut start = Position { row: 0, col: 0 };
+            start[dir.perp()] = i;
+            
+            let mut word: Vec<LetterTile> = vec![];
+            let mut word_start = Placement(start, dir);
+            let mut current = Some(Placement(start, dir));
+            loop {
+                let tile = current.and_then(|place| board.letter_table.get(place.0)).and_then(Square::tile);
+                if let Some(&tile) = tile {
+                    if word.is_empty() {
+                        word_start = current.unwrap();
+                    }
+                    word.push(tile);
+                } else {
+                    if word.len() >= 2 && !matches_a_word(&word, dictionary) {
+                        invalid.push((word_start, word.iter().map(|tile| match tile {
+                            LetterTile::Letter(l) => l.0 as char,
+                            LetterTile::Wildcard => '*',
+                        }).collect()));
+                    }
+                    word.clear();
+                }
+                
+                match current {
+                    Some(place) => current = place.try_next(board.letter_table.len_in(dir)),
+                    None => break,
+                }
+            }
+        }
+    }
+    
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(invalid)
+    }
+}
+
+/// The words formed by the move in its perpendicular direction that are not in the
+/// dictionary, with the position of the tile of the move in each of them
+///
+/// A single tile has no direction, so both its words are checked. Wildcards (`*`)
+/// accept any letter. The move is not checked to fit on the board (see `Move::is_structurally_valid`)
+pub fn cross_word_failures(board: &Board, a_move: &Move, dictionary: &fst::Set<impl AsRef<[u8]>>) -> Vec<(Position, String)> {
+    perpendicular_words(board, a_move).into_iter()
+        .filter(|(_, word)| !matches_a_word(word, dictionary))
+        .map(|(pos, word)| (pos, word.iter().map(LetterTile::to_string).collect()))
+        .collect()
+}
+
+/// The words formed by the move in its perpendicular direction, where the wildcards are `*`
+///
+/// A single tile has no direction, so both its words are given
+pub fn cross_words(board: &Board, a_move: &Move) -> Vec<String> {
+    perpendicular_words(board, a_move).into_iter()
+        .map(|(_, word)| word.iter().map(LetterTile::to_string).collect())
+        .collect()
+}
+
+/// The words of two tiles or more formed in the perpendicular direction of the move
+/// (both directions for a single tile), with the position of the tile of the move in each of them
+fn perpendicular_words(board: &Board, a_move: &Move) -> Vec<(Position, Vec<LetterTile>)> {
+    let placed = a_move.positions().zip(a_move.placed_tiles()).collect::<Vec<_>>();
+    let dirs = match a_move {
+        Move::SingleLetter(_, _) => vec![Direction::Horizontal, Direction::Vertical],
+        Move::MultiLetters(place, _, _) => vec![place.1.perp()],
+    };
+    
+    let mut words = vec![];
+    for &(pos, _) in &placed {
+        for &dir in &dirs {
+            let word = word_through(board, &placed, pos, dir);
+            if word.len() >= 2 {
+                words.push((pos, word));
+            }
+        }
+    }
+    words
+}
+
+/// The letters of the tiles placed by the move, where the wildcards are the letter
+/// they have in `word` (the word played in the direction of the move, or in any
+/// direction for a single tile)
+pub fn resolved_tiles(board: &Board, a_move: &Move, word: &str) -> Option<Vec<LetterTile>> {
+    let placed = a_move.positions().zip(a_move.placed_tiles()).collect::<Vec<_>>();
+    let dirs = match a_move {
+        Move::SingleLetter(_, _) => vec![Direction::Horizontal, Direction::Vertical],
+        Move::MultiLetters(place, _, _) => vec![place.1],
+    };
+    let filled = |pos| board.letter_table.get(pos).and_then(Square::tile).copied();
+    
+    dirs.into_iter().find_map(|dir| {
+        let size = board.letter_table.len_in(dir);
+        let mut start = Placement(placed[0].0, dir);
+        while let Some(back) = start.try_back(size).filter(|back| filled(back.0).is_some()) {
+            start = back;
+        }
+        
+        let mut letters = vec![];
+        let mut place = Some(start);
+        for byte in word.bytes() {
+            let pos = place?.0;
+            match (placed.iter().find(|(placed_pos, _)| *placed_pos == pos), filled(pos)) {
+                (Some((_, LetterTile::Wildcard)), _) => letters.push(LetterTile::Letter(Letter(byte))),
+                (Some((_, LetterTile::Letter(l))), _) if l.0 == byte => letters.push(LetterTile::Letter(*l)),
+                (None, Some(LetterTile::Wildcard)) => {},
+                (None, Some(LetterTile::Letter(l))) if l.0 == byte => {},
+                _ => return None,
+            }
+            place = place?.try_next(size);
+        }
+        let ends = place.is_none_or(|place| filled(place.0).is_none());
+        
+        if ends && letters.len() == placed.len() {
+            Some(letters)
+        } else {
+            None
+        }
+    })
+}
+
+/// The tiles of the word going through `pos` in the direction `dir`, once the `placed` tiles are on the board
+pub(crate) fn word_through(board: &Board, placed: &[(Position, LetterTile)], pos: Position, dir: Direction) -> Vec<LetterTile> {
+    let tile_at = |pos| placed.iter().find(|(placed_pos, _)| *placed_pos == pos).map(|(_, tile)| *tile)
+        .or_else(|| board.letter_table.get(pos).and_then(Square::tile).copied());
+    
+    let size = board.letter_table.len_in(dir);
+    let mut start = Placement(pos, dir);
+    while let Some(back) = start.try_back(size).filter(|back| tile_at(back.0).is_some()) {
+        start = back;
+    }
+    std::iter::successors(Some(start), |place| place.try_next(size))
+        .map_while(|place| tile_at(place.0))
+        .collect()
+}
+
+pub(crate) fn matches_a_word(word: &[LetterTile], dictionary: &fst::Set<impl AsRef<[u8]>>) -> bool {
+    use fst::{Automaton, IntoStreamer, Streamer};
+    
+    struct Pattern<'a>(&'a [LetterTile]);
+    
+    impl<'a> Automaton for Pattern<'a> {
+        type State = Option<usize>;
+        fn start(&self) -> Self::State {
+            Some(0)
+        }
+        fn is_match(&self, state: &Self::State) -> bool {
+            *state == Some(self.0.len())
+        }
+        fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+            state.filter(|&i| match self.0.get(i) {
+                None => false,
+                Some(LetterTile::Wildcard) => true,
+                Some(LetterTile::Letter(l)) => l.0 == byte,
+            }).map(|i| i + 1)
+        }
+        fn can_match(&self, state: &Self::State) -> bool {
+            state.is_some()
+        }
+    }
+    
+    dictionary.search(Pattern(word)).into_stream().next().is_some()
+}
+
+/// How a position is written, the letter being the row or the column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+pub enum PositionFormat {
+    /// The column as a letter, then the row as a number (`H-8`)
+    #[default]
+    LetterDigit,
+    /// The column as a number, then the row as a letter (`8-H`)
+    DigitLetter,
+}
+
+impl PositionFormat {
+    pub fn format(&self, pos: &Position) -> String {
+        match self {
+            Self::LetterDigit => format!("{:>2}-{:<2}", (b'A' + pos.col as u8) as char, pos.row+1),
+            Self::DigitLetter => format!("{:>2}-{:<2}", pos.col+1, (b'A' + pos.row as u8) as char),
+        }
+    }
+    
+    /// Reads a position written in this format, with or without the dash (`H8`, `H-8`, ` h - 8 `)
+    ///
+    /// None if the position is not on the board
+    pub fn parse(&self, s: &str) -> Option<Position> {
+        let s = s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+        let (letter, number) = match self {
+            Self::LetterDigit => {
+                let letter = s.chars().next()?;
+                (letter, s[letter.len_utf8()..].trim_start_matches('-'))
+            },
+            Self::DigitLetter => {
+                let letter = s.chars().next_back()?;
+                (letter, s[..s.len() - letter.len_utf8()].trim_end_matches('-'))
+            },
+        };
+        
+        if !letter.is_ascii_alphabetic() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None
+        }
+        let letter = (letter.to_ascii_uppercase() as u8 - b'A') as usize;
+        let number = number.parse::<usize>().ok()?.checked_sub(1)?;
+        if letter >= BOARD_SIZE || number >= BOARD_SIZE {
+            return None
+        }
+        
+        Some(match self {
+            Self::LetterDigit => Position { row: number, col: letter },
+            Self::DigitLetter => Position { row: letter, col: number },
+        })
+    }
+}
+
+/// The coordinates of the usual scrabble notation, that also give the direction of the move
+///
+/// The row number comes first for a horizontal move (`8H`), and the column letter
+/// first for a vertical one (`H8`), as in GCG transcripts
+pub struct CoordNotation;
+
+impl CoordNotation {
+    /// Reads the coordinates of a move, None if they are not on the board
+    pub fn parse(s: &str) -> Option<Placement> {
+        let s = s.trim();
+        if s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Some(Placement(PositionFormat::LetterDigit.parse(s)?, Direction::Vertical))
+        } else {
+            // this format has the letter as the row, and the number as the column
+            let transposed = PositionFormat::DigitLetter.parse(s)?;
+            Some(Placement(Position { row: transposed.col, col: transposed.row }, Direction::Horizontal))
+        }
+    }
+    
+    pub fn format(placement: &Placement) -> String {
+        let Placement(pos, dir) = placement;
+        let col = (b'A' + pos.col as u8) as char;
+        match dir {
+            Direction::Horizontal => format!("{}{}", pos.row + 1, col),
+            Direction::Vertical => format!("{}{}", col, pos.row + 1),
+        }
+    }
+}
+
+/// The characters used to write a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardFormat {
+    empty_chars: Vec<u8>,
+}
+
+impl BoardFormat {
+    /// A format where the `empty_chars` mean an empty square
+    ///
+    /// Fails with the first character that is a letter, the wildcard `*`, or not ascii
+    pub fn with_empty_chars(empty_chars: &[u8]) -> Result<Self, u8> {
+        match empty_chars.iter().find(|b| b.is_ascii_alphabetic() || **b == b'*' || !b.is_ascii()) {
+            Some(&invalid) => Err(invalid),
+            None => Ok(Self { empty_chars: empty_chars.to_vec() }),
+        }
+    }
+}
+
+impl Default for BoardFormat {
+    /// Spaces and underscores are empty squares
+    fn default() -> Self {
+        Self { empty_chars: b" _".to_vec() }
+    }
+}
+
+/// How to handle what is not understood in the inputs
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Strictness {
+    /// Log a warning and ignore it
+    #[default]
+    Lenient,
+    /// Return an error
+    Strict,
+}
+
+/// Why a board cannot be read by `Board::parse`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardParseError {
+    UnknownCharacters(Vec<UnknownCharacter>),
+    /// The first tile that does not fit on the board
+    OutOfBoard(Position),
+}
+
+/// A character that is not understood when reading a board or a tray
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnknownCharacter {
+    pub byte: u8,
+    /// The line of the character (always 0 for a tray)
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -194,8 +1103,29 @@ pub struct Table<T> {
 
 impl<T> Table<T> {
     pub fn fill_with(el: T) -> Self where T: Clone {
+        Self::with_size(BOARD_SIZE, BOARD_SIZE, el)
+    }
+    
+    /// A table of `rows` rows of `cols` squares, for the boards that are not the usual square
+    pub fn with_size(rows: usize, cols: usize, el: T) -> Self where T: Clone {
         Self {
-            squares: vec![vec![el; BOARD_SIZE]; BOARD_SIZE],
+            squares: vec![vec![el; cols]; rows],
+        }
+    }
+    
+    /// The number of squares of a line in the direction `dir` (the number of rows for `Vertical`)
+    pub fn len_in(&self, dir: Direction) -> usize {
+        match dir {
+            Direction::Vertical => self.squares.len(),
+            Direction::Horizontal => self.squares.first().map_or(0, Vec::len),
+        }
+    }
+    
+    /// The square in the middle, where the first move is played
+    pub fn center(&self) -> Position {
+        Position {
+            row: self.len_in(Direction::Vertical) / 2,
+            col: self.len_in(Direction::Horizontal) / 2,
         }
     }
     
@@ -208,6 +1138,13 @@ impl<T> Table<T> {
     pub fn set(&mut self, pos: Position, val: T) {
         self.squares[pos.row][pos.col] = val
     }
+    
+    /// All the squares of the table, row by row
+    pub fn iter_positions(&self) -> impl Iterator<Item=(Position, &T)> {
+        self.squares.iter().enumerate().flat_map(|(row, line)| {
+            line.iter().enumerate().map(move |(col, el)| (Position { row, col }, el))
+        })
+    }
 }
 
 
@@ -268,3 +1205,313 @@ fn test_alignement() {
         None,
     );
 }
+
+#[test]
+fn placement_edges() {
+    let corner = Placement(Position { row: 0, col: 14 }, Direction::Horizontal);
+    
+    assert_eq!(corner.try_next(BOARD_SIZE), None);
+    assert_eq!(corner.try_back(BOARD_SIZE), Some(Placement(Position { row: 0, col: 13 }, Direction::Horizontal)));
+    assert_eq!(corner.perp().try_back(BOARD_SIZE), None);
+    assert_eq!(corner.perp().try_next(BOARD_SIZE), Some(Placement(Position { row: 1, col: 14 }, Direction::Vertical)));
+}
+
+#[test]
+fn verify_board_words() {
+    let dictionary = build_dictionary(vec!["cat", "at", "as"]).unwrap();
+    
+    let board = Board::parse("cat\n  s\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    assert_eq!(verify_all_words(&board, &dictionary), Err(vec![
+        (Placement(Position { row: 0, col: 2 }, Direction::Vertical), "ts".to_owned()),
+    ]));
+    
+    // the wildcards can be "a"
+    let board = Board::parse("c*t\n *s\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    assert_eq!(verify_all_words(&board, &dictionary), Err(vec![
+        (Placement(Position { row: 0, col: 2 }, Direction::Vertical), "ts".to_owned()),
+    ]));
+    
+    let board = Board::parse("cat\n s\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    assert_eq!(verify_all_words(&board, &dictionary), Ok(()));
+}
+
+#[test]
+fn diff_to_move() {
+    let format = BoardFormat::default();
+    let before = Board::parse("\n c t\n", &format, Strictness::Strict).unwrap();
+    let after = Board::parse(" s\n cAts\n", &format, Strictness::Strict).unwrap();
+    
+    assert_eq!(board_diff(&before, &after).len(), 3);
+    assert_eq!(board_diff(&before, &after)[0], (
+        Position { row: 0, col: 1 },
+        Square::Empty,
+        Square::Filled(LetterTile::Letter(Letter(b's'))),
+    ));
+    
+    // the tiles are not on a single line
+    assert_eq!(move_from_diff(&before, &after), None);
+    
+    let after = Board::parse("\n cAts\n", &format, Strictness::Strict).unwrap();
+    assert_eq!(move_from_diff(&before, &after), Some(OwnedMove::MultiLetters(
+        Placement(Position { row: 1, col: 2 }, Direction::Horizontal),
+        LetterTile::Wildcard,
+        vec![(1, LetterTile::Letter(Letter(b's')))],
+    )));
+    
+    // the square between the tiles is empty
+    let after = Board::parse("\n c ta s\n", &format, Strictness::Strict).unwrap();
+    assert_eq!(move_from_diff(&before, &after), None);
+    
+    let arena = typed_arena::Arena::new();
+    let s = LetterTile::Letter(Letter(b's'));
+    assert_eq!(
+        move_from_placed(&before, &[(Position { row: 1, col: 4 }, s)], &arena),
+        Some(Move::SingleLetter(Position { row: 1, col: 4 }, s)),
+    );
+    // a tile is already there
+    assert_eq!(move_from_placed(&before, &[(Position { row: 1, col: 3 }, s)], &arena), None);
+    assert_eq!(move_from_placed(&before, &[], &arena), None);
+}
+
+#[test]
+fn bytes_round_trip() {
+    use solver::word_finder::TrayRemaining;
+    
+    let board = Board::parse("cAt\n  *\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    let bytes = board.to_bytes().unwrap();
+    assert_eq!(bytes.len(), 2 + BOARD_SIZE * BOARD_SIZE);
+    assert_eq!(&bytes[2..5], &[3, 0x81, 20]);
+    let read = Board::from_bytes(&bytes).unwrap();
+    assert_eq!(board_diff(&board, &read), vec![]);
+    assert_eq!(read.value_table.get(Position { row: 0, col: 1 }), Some(&Square::Filled(LetterTile::Wildcard)));
+    assert!(Board::from_bytes(&bytes[1..]).is_none());
+    
+    let tray = TrayRemaining::parse("trean*o", Strictness::Strict).unwrap();
+    let read = TrayRemaining::from_bytes(&tray.to_bytes().unwrap()).unwrap();
+    assert_eq!(format!("{:?}", read), format!("{:?}", tray));
+}
+
+#[test]
+fn coordinate_notation() {
+    let horizontal = Placement(Position { row: 7, col: 0 }, Direction::Horizontal);
+    let vertical = Placement(Position { row: 7, col: 0 }, Direction::Vertical);
+    assert_eq!(CoordNotation::format(&horizontal), "8A");
+    assert_eq!(CoordNotation::format(&vertical), "A8");
+    assert_eq!(CoordNotation::parse("8A"), Some(horizontal));
+    assert_eq!(CoordNotation::parse(" a8 "), Some(vertical));
+    
+    let last = Placement(Position { row: 14, col: 14 }, Direction::Horizontal);
+    assert_eq!(CoordNotation::format(&last), "15O");
+    assert_eq!(CoordNotation::parse("15O"), Some(last));
+    assert_eq!(CoordNotation::parse("O15"), Some(Placement(Position { row: 14, col: 14 }, Direction::Vertical)));
+    
+    assert_eq!(CoordNotation::parse("8P"), None);
+    assert_eq!(CoordNotation::parse("P8"), None);
+    assert_eq!(CoordNotation::parse("16A"), None);
+    assert_eq!(CoordNotation::parse("H"), None);
+}
+
+#[test]
+fn parse_positions() {
+    let pos = Position { row: 7, col: 1 };
+    for &format in &[PositionFormat::LetterDigit, PositionFormat::DigitLetter] {
+        assert_eq!(format.parse(&format.format(&pos)), Some(pos));
+    }
+    
+    assert_eq!(PositionFormat::LetterDigit.parse("H8"), Some(Position { row: 7, col: 7 }));
+    assert_eq!(PositionFormat::LetterDigit.parse(" o - 15 "), Some(Position { row: 14, col: 14 }));
+    assert_eq!(PositionFormat::DigitLetter.parse("15-A"), Some(Position { row: 0, col: 14 }));
+    assert_eq!(PositionFormat::LetterDigit.parse("P1"), None);
+    assert_eq!(PositionFormat::LetterDigit.parse("A0"), None);
+    assert_eq!(PositionFormat::LetterDigit.parse("A+1"), None);
+    assert_eq!(PositionFormat::DigitLetter.parse("H8"), None);
+}
+
+#[test]
+fn structurally_valid_moves() {
+    let mut board = Board::empty();
+    board.letter_table.set(Position { row: 7, col: 8 }, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+    
+    let tile = LetterTile::Letter(Letter(b't'));
+    let place = Placement(Position { row: 7, col: 7 }, Direction::Horizontal);
+    assert!(Move::MultiLetters(place, tile, &[(1, tile)]).is_structurally_valid(&board));
+    // the skipped square is not filled, or the tile is placed on a filled one
+    assert!(!Move::MultiLetters(place, tile, &[(2, tile)]).is_structurally_valid(&board));
+    assert!(!Move::MultiLetters(place, tile, &[(0, tile)]).is_structurally_valid(&board));
+    // out of the board
+    let place = Placement(Position { row: 7, col: 14 }, Direction::Horizontal);
+    assert!(!Move::MultiLetters(place, tile, &[(0, tile)]).is_structurally_valid(&board));
+    assert!(!Move::SingleLetter(Position { row: 7, col: 8 }, tile).is_structurally_valid(&board));
+}
+
+#[test]
+fn display_moves() {
+    let tile = |l| LetterTile::Letter(Letter(l));
+    let place = Placement(Position { row: 7, col: 7 }, Direction::Horizontal);
+    assert_eq!(Move::MultiLetters(place, tile(b'c'), &[(1, tile(b't'))]).to_string(), "H-8  →, c_t");
+    assert_eq!(
+        Move::MultiLetters(place, LetterTile::Wildcard, &[(0, tile(b'a'))]).format(PositionFormat::DigitLetter),
+        " 8-H  →, *a",
+    );
+    assert_eq!(OwnedMove::SingleLetter(Position { row: 0, col: 0 }, tile(b'a')).to_string(), "A-1 ,   a");
+}
+
+#[test]
+fn ordered_moves() {
+    let tile = |l| LetterTile::Letter(Letter(l));
+    let others = [(0, tile(b't'))];
+    let other_others = [(1, tile(b't'))];
+    let horizontal = Placement(Position { row: 7, col: 7 }, Direction::Horizontal);
+    let vertical = Placement(Position { row: 7, col: 7 }, Direction::Vertical);
+    
+    let sorted = vec![
+        Move::SingleLetter(Position { row: 0, col: 14 }, tile(b'a')),
+        Move::SingleLetter(Position { row: 1, col: 0 }, LetterTile::Wildcard),
+        Move::SingleLetter(Position { row: 1, col: 0 }, tile(b'a')),
+        Move::MultiLetters(vertical, tile(b'a'), &others),
+        Move::MultiLetters(horizontal, tile(b'a'), &others),
+        Move::MultiLetters(horizontal, tile(b'a'), &other_others),
+        Move::MultiLetters(horizontal, tile(b'b'), &others),
+    ];
+    
+    let mut moves = sorted.clone();
+    moves.reverse();
+    moves.sort();
+    assert_eq!(moves, sorted);
+    
+    let mut moves = sorted.clone();
+    moves.rotate_left(3);
+    moves.sort();
+    assert_eq!(moves, sorted);
+    
+    let owned = sorted.iter().map(OwnedMove::from).collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(owned.into_iter().collect::<Vec<_>>(), sorted.iter().map(OwnedMove::from).collect::<Vec<_>>());
+}
+
+#[test]
+fn move_footprint() {
+    let board = Board::parse("\n\n\n\n\n\n\n       a\n      o\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    let pos = |row, col| Position { row, col };
+    let tile = |l| LetterTile::Letter(Letter(l));
+    
+    // "cat" through the `a`, and "co" with the `o` below the `c`
+    let others = [(1, tile(b't'))];
+    let cat = Move::MultiLetters(Placement(pos(7, 6), Direction::Horizontal), tile(b'c'), &others);
+    assert_eq!(cat.footprint(&board, false), vec![pos(7, 6), pos(7, 7), pos(7, 8)]);
+    assert_eq!(cat.footprint(&board, true), vec![pos(7, 6), pos(7, 7), pos(7, 8), pos(8, 6)]);
+    
+    // a single tile forming "ta" horizontally and "to" vertically
+    let t = Move::SingleLetter(pos(7, 6), tile(b't'));
+    assert_eq!(t.footprint(&board, false), vec![pos(7, 6), pos(7, 7), pos(8, 6)]);
+}
+
+#[test]
+fn failed_cross_words() {
+    let board = Board::parse("\n\n\n\n\n\n\n      cat\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    let tile = |l| LetterTile::Letter(Letter(l));
+    
+    // "to" below "at" of "cat", forming "at" and "to"
+    let others = [(0, tile(b'o'))];
+    let to = Move::MultiLetters(Placement(Position { row: 8, col: 7 }, Direction::Horizontal), tile(b't'), &others);
+    
+    let dictionary = build_dictionary(vec!["at", "cat", "to"]).unwrap();
+    assert_eq!(cross_word_failures(&board, &to, &dictionary), vec![]);
+    
+    let dictionary = build_dictionary(vec!["cat", "to"]).unwrap();
+    assert_eq!(cross_word_failures(&board, &to, &dictionary), vec![(Position { row: 8, col: 7 }, "at".to_owned())]);
+    
+    // a single tile is checked in both directions
+    let s = Move::SingleLetter(Position { row: 8, col: 8 }, LetterTile::Wildcard);
+    assert_eq!(cross_word_failures(&board, &s, &dictionary), vec![]);
+    let dictionary = build_dictionary(vec!["cat"]).unwrap();
+    assert_eq!(cross_word_failures(&board, &s, &dictionary), vec![(Position { row: 8, col: 8 }, "t*".to_owned())]);
+}
+
+#[test]
+fn several_dictionaries() {
+    let dictionaries = vec![
+        ("twl".to_owned(), build_dictionary(vec!["cat", "qi"]).unwrap()),
+        ("sowpods".to_owned(), build_dictionary(vec!["cat", "za"]).unwrap()),
+    ];
+    
+    let union = union_dictionaries(dictionaries.iter().map(|(_, dictionary)| dictionary));
+    assert_eq!(union.stream().into_strs().unwrap(), vec!["cat", "qi", "za"]);
+    
+    assert_eq!(word_sources("cat", &dictionaries), vec!["twl", "sowpods"]);
+    assert_eq!(word_sources("za", &dictionaries), vec!["sowpods"]);
+    assert_eq!(word_sources("dog", &dictionaries), Vec::<&str>::new());
+}
+
+#[test]
+fn wildcard_letters_of_a_move() {
+    let mut board = Board::empty();
+    board.letter_table.set(Position { row: 7, col: 7 }, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+    
+    let others = [(1, LetterTile::Letter(Letter(b't')))];
+    let a_move = Move::MultiLetters(
+        Placement(Position { row: 7, col: 6 }, Direction::Horizontal),
+        LetterTile::Wildcard,
+        &others,
+    );
+    assert_eq!(
+        resolved_tiles(&board, &a_move, "cat"),
+        Some(vec![LetterTile::Letter(Letter(b'c')), LetterTile::Letter(Letter(b't'))]),
+    );
+    assert_eq!(resolved_tiles(&board, &a_move, "cart"), None);
+    
+    // a single tile can make a word in both directions
+    let a_move = Move::SingleLetter(Position { row: 8, col: 7 }, LetterTile::Wildcard);
+    assert_eq!(resolved_tiles(&board, &a_move, "at"), Some(vec![LetterTile::Letter(Letter(b't'))]));
+}
+
+#[test]
+fn filled_squares() {
+    let board = Board::empty();
+    assert!(board.is_empty());
+    assert_eq!(board.filled_positions(), vec![]);
+    
+    let board = Board::parse("\n  a\n*", &BoardFormat::default(), Strictness::Strict).unwrap();
+    assert!(!board.is_empty());
+    assert_eq!(board.filled_positions(), vec![Position { row: 1, col: 2 }, Position { row: 2, col: 0 }]);
+}
+
+#[test]
+fn parse_strictness() {
+    let board = "ab\n c.d\n";
+    let format = BoardFormat::default();
+    
+    let lenient = Board::parse(board, &format, Strictness::Lenient).unwrap();
+    assert_eq!(lenient.letter_table.get(Position { row: 1, col: 3 }), Some(&Square::Filled(LetterTile::Letter(Letter(b'd')))));
+    assert_eq!(lenient.letter_table.get(Position { row: 1, col: 2 }), Some(&Square::Empty));
+    
+    assert_eq!(
+        Board::parse(board, &format, Strictness::Strict).unwrap_err(),
+        BoardParseError::UnknownCharacters(vec![UnknownCharacter { byte: b'.', line: 1, column: 2 }]),
+    );
+    
+    let dots = BoardFormat::with_empty_chars(b".").unwrap();
+    assert!(Board::parse(board, &dots, Strictness::Strict).is_err());
+    assert!(Board::parse("ab\n.c.d\n", &dots, Strictness::Strict).is_ok());
+    
+    // a tile after the end of a row, or below the last row, but not empty squares
+    let long_row = format!("{}ab", " ".repeat(14));
+    assert_eq!(Board::parse(&long_row, &format, Strictness::Lenient).unwrap_err(), BoardParseError::OutOfBoard(Position { row: 0, col: 15 }));
+    assert!(Board::parse(&format!("{}a  ", " ".repeat(14)), &format, Strictness::Strict).is_ok());
+    assert_eq!(
+        Board::parse_with_size(&format!("{}a", "\n".repeat(11)), 11, 15, &format, Strictness::Strict).unwrap_err(),
+        BoardParseError::OutOfBoard(Position { row: 11, col: 0 }),
+    );
+    
+    assert_eq!(BoardFormat::with_empty_chars(b" *"), Err(b'*'));
+    assert_eq!(BoardFormat::with_empty_chars(b".a"), Err(b'a'));
+}
+
+#[test]
+fn test_build_dictionary() {
+    let dictionary = build_dictionary(vec!["cat", " Dog\r", "", "cat"]).unwrap();
+    assert_eq!(dictionary.len(), 2);
+    assert!(dictionary.contains("cat"));
+    assert!(dictionary.contains("dog"));
+    
+    assert_eq!(build_dictionary(vec!["cat", "it's"]).unwrap_err(), InvalidWord("it's".to_owned()));
+}
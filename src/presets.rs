@@ -0,0 +1,250 @@
+
+//! The rules that go together for the usual game variants
+
+use std::collections::HashMap;
+
+use super::{Board, UnknownCharacter, BOARD_SIZE};
+use super::score_rules::{
+    ScoreRules,
+    LetterScoring,
+    BoardBonus,
+    ArrayLetterScore,
+    GridBonus,
+    EnglishScrabbleScoring,
+    EnglishWordsWithFriendsScoring,
+    FrenchScrabbleScoring,
+    ScrabbleBonus,
+    WordsWithFriendsBonus,
+};
+use super::solver::word_finder::TrayRemaining;
+
+/// The scores, the board and the tiles of a game variant
+pub struct Preset<Scoring: LetterScoring, Bonuses: BoardBonus> {
+    pub score_rules: ScoreRules<Scoring, Bonuses>,
+    /// All the tiles of the game
+    pub bag: TrayRemaining,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl<Scoring: LetterScoring, Bonuses: BoardBonus> Preset<Scoring, Bonuses> {
+    /// An empty board of the size of the variant
+    pub fn board(&self) -> Board {
+        Board::with_size(self.rows, self.cols)
+    }
+}
+
+/// The names accepted by the `variant` config, in the order of the functions of this module
+pub const NAMES: [&str; 4] = ["scrabble_english", "words_with_friends", "french_scrabble", "super_scrabble"];
+
+fn preset<Scoring: LetterScoring, Bonuses: BoardBonus>(
+    scoring: Scoring, bonuses: Bonuses, extra_bonus: u32, bag: &str,
+) -> Preset<Scoring, Bonuses> {
+    Preset {
+        score_rules: ScoreRules::new(scoring, bonuses, extra_bonus),
+        bag: TrayRemaining::from_compact_str(bag).unwrap(),
+        rows: BOARD_SIZE,
+        cols: BOARD_SIZE,
+    }
+}
+
+/// English Scrabble, with 100 tiles
+pub fn scrabble_english() -> Preset<EnglishScrabbleScoring, ScrabbleBonus> {
+    preset(
+        EnglishScrabbleScoring, ScrabbleBonus, 50,
+        "a9b2c2d4e12f2g3h2i9j1k1l4m2n6o8p2q1r6s4t6u4v2w2x1y2z1*2",
+    )
+}
+
+/// Words With Friends, with 104 tiles and 35 points for a bingo
+pub fn words_with_friends() -> Preset<EnglishWordsWithFriendsScoring, WordsWithFriendsBonus> {
+    preset(
+        EnglishWordsWithFriendsScoring, WordsWithFriendsBonus, 35,
+        "a9b2c2d5e13f2g3h4i8j1k1l4m2n5o8p2q1r6s5t7u4v2w2x1y2z1*2",
+    )
+}
+
+/// French Scrabble, with 102 tiles (accented letters are played as the letter without accent)
+pub fn french_scrabble() -> Preset<FrenchScrabbleScoring, ScrabbleBonus> {
+    preset(
+        FrenchScrabbleScoring, ScrabbleBonus, 50,
+        "a9b2c2d3e15f2g2h2i8j1k1l5m3n6o6p2q1r6s6t6u6v2w1x1y1z1*2",
+    )
+}
+
+/// The bonuses of Super Scrabble, in the format of `GridBonus::parse`
+const SUPER_SCRABBLE_PREMIUMS: [&str; 21] = [
+    "Q..d...T..d..T...d..Q",
+    ".D..t...q...q...t..D.",
+    "..D..q...d.d...q..D..",
+    "d..D..d...T...d..D..d",
+    ".t..D...t...t...D..t.",
+    "..q..D...d.d...D..q..",
+    "...d..D...d...D..d...",
+    "T......D.....D......T",
+    ".q..t...t...t...t..q.",
+    "..d..d...d.d...d..d..",
+    "d..T..d...D...d..T..d",
+    "..d..d...d.d...d..d..",
+    ".q..t...t...t...t..q.",
+    "T......D.....D......T",
+    "...d..D...d...D..d...",
+    "..q..D...d.d...D..q..",
+    ".t..D...t...t...D..t.",
+    "d..D..d...T...d..D..d",
+    "..D..q...d.d...q..D..",
+    ".D..t...q...q...t..D.",
+    "Q..d...T..d..T...d..Q",
+];
+
+/// Super Scrabble, with 200 tiles on a board of 21x21 squares
+pub fn super_scrabble() -> Preset<EnglishScrabbleScoring, GridBonus> {
+    let bonuses = GridBonus::parse(&SUPER_SCRABBLE_PREMIUMS).unwrap();
+    let (rows, cols) = bonuses.size();
+    Preset {
+        rows,
+        cols,
+        ..preset(
+            EnglishScrabbleScoring, bonuses, 50,
+            "a16b4c6d8e24f4g5h5i13j2k2l7m6n13o15p4q2r13s10t15u7v3w4x2y4z2*4",
+        )
+    }
+}
+
+/// A whole game definition, read from a file (for example a `ruleset.json`)
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Ruleset {
+    /// The score of each letter, and of the wildcard (`*`)
+    pub letter_score: HashMap<char, u32>,
+    /// The bonus of each square, one string per row (see `GridBonus::parse`)
+    pub premiums: Vec<String>,
+    /// All the tiles of the game, in the format of `TrayRemaining::from_compact_str`
+    pub distribution: String,
+    /// The bonus of a bingo
+    pub extra_bonus: u32,
+    /// The number of tiles in a tray
+    pub rack_size: usize,
+}
+
+/// Why a `Ruleset` cannot be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesetError {
+    /// A character of `letter_score` is neither an ascii letter nor `*`
+    LetterScore(char),
+    Premiums(UnknownCharacter),
+    /// The first row of `premiums` that does not have as many squares as the longest one
+    RaggedPremiums(usize),
+    Distribution(UnknownCharacter),
+}
+
+impl Ruleset {
+    pub fn into_preset(self) -> Result<Preset<ArrayLetterScore, GridBonus>, RulesetError> {
+        let scoring = ArrayLetterScore::new(&self.letter_score).map_err(RulesetError::LetterScore)?;
+        let bonuses = GridBonus::parse(&self.premiums).map_err(RulesetError::Premiums)?;
+        let (rows, cols) = bonuses.size();
+        if let Some(row) = self.premiums.iter().position(|row| row.len() != cols) {
+            return Err(RulesetError::RaggedPremiums(row))
+        }
+        let bag = TrayRemaining::from_compact_str(&self.distribution).map_err(RulesetError::Distribution)?;
+        let mut score_rules = ScoreRules::new(scoring, bonuses, self.extra_bonus);
+        score_rules.rack_size = self.rack_size;
+        Ok(Preset {
+            score_rules,
+            bag,
+            rows,
+            cols,
+        })
+    }
+}
+
+#[test]
+fn bags() {
+    assert_eq!(scrabble_english().bag.tiles().count(), 100);
+    assert_eq!(words_with_friends().bag.tiles().count(), 104);
+    assert_eq!(french_scrabble().bag.tiles().count(), 102);
+    assert_eq!(super_scrabble().bag.tiles().count(), 200);
+}
+
+#[test]
+fn super_scrabble_bonuses() {
+    use crate::Position;
+    
+    let preset = super_scrabble();
+    assert_eq!((preset.rows, preset.cols), (21, 21));
+    
+    let bonuses = &preset.score_rules.bonuses;
+    let bonus = |row, col| bonuses.bonus_at(Position { row, col });
+    for row in 0..21 {
+        for col in 0..21 {
+            // symmetrical like the usual board
+            assert_eq!(bonus(row, col), bonus(col, row));
+            assert_eq!(bonus(row, col), bonus(20 - row, col));
+        }
+    }
+    assert_eq!(bonus(0, 0).word, 4);
+    assert_eq!(bonus(1, 8).letter, 4);
+    assert_eq!(bonus(0, 7).word, 3);
+    assert_eq!(bonus(10, 10).word, 2);
+    
+    // a word out of the usual board
+    let board = preset.board();
+    let play = crate::Move::SingleLetter(Position { row: 20, col: 20 }, crate::LetterTile::Letter(crate::Letter(b'z')));
+    assert_eq!(crate::solver::score::naive_score(&board.value_table, &play, &preset.score_rules), 10 * 4);
+}
+
+#[test]
+fn words_with_friends_bonuses() {
+    use crate::Position;
+    
+    let bonuses = words_with_friends().score_rules.bonuses;
+    let word = |row, col| bonuses.bonus_at(Position { row, col }).word;
+    let letter = |row, col| bonuses.bonus_at(Position { row, col }).letter;
+    
+    // no bonus on the center, and the triple word squares of the first row
+    assert_eq!((word(7, 7), letter(7, 7)), (1, 1));
+    assert_eq!(word(0, 3), 3);
+    assert_eq!(word(0, 11), 3);
+    assert_eq!(word(14, 3), 3);
+    assert_eq!(word(11, 14), 3);
+    assert_eq!(letter(0, 6), 3);
+    assert_eq!(word(3, 7), 2);
+    assert_eq!(letter(1, 2), 2);
+    assert_eq!(letter(2, 1), 2);
+    
+    let n_bonuses = (0..BOARD_SIZE).flat_map(|row| (0..BOARD_SIZE).map(move |col| Position { row, col }))
+        .filter(|&pos| {
+            let bonus = bonuses.bonus_at(pos);
+            bonus.word > 1 || bonus.letter > 1
+        })
+        .count();
+    assert_eq!(n_bonuses, 8 + 16 + 12 + 24);
+}
+
+#[test]
+fn ruleset_file() {
+    use crate::{LetterTile, Letter, Position};
+    
+    let json = r#"{
+        "letter_score": { "a": 1, "b": 3, "*": 0 },
+        "premiums": ["T.T", ".D.", "T.T"],
+        "distribution": "a5b2*1",
+        "extra_bonus": 20,
+        "rack_size": 5
+    }"#;
+    let mut config = config::Config::new();
+    config.merge(config::File::from_str(json, config::FileFormat::Json)).unwrap();
+    let ruleset: Ruleset = config.try_into().unwrap();
+    
+    let preset = ruleset.clone().into_preset().unwrap();
+    assert_eq!((preset.rows, preset.cols, preset.score_rules.rack_size), (3, 3, 5));
+    assert_eq!(preset.bag.tiles().count(), 8);
+    assert_eq!(preset.score_rules.extra_bonus, 20);
+    assert_eq!(preset.score_rules.scoring.score_for(&LetterTile::Letter(Letter(b'b'))), 3);
+    assert!(!preset.score_rules.scoring.has_score_for(&LetterTile::Letter(Letter(b'c'))));
+    assert_eq!(preset.score_rules.bonuses.bonus_at(Position { row: 1, col: 1 }).word, 2);
+    
+    let wrong = Ruleset { premiums: vec!["T.T".to_owned(), ".D".to_owned(), "T.T".to_owned()], ..ruleset.clone() };
+    assert_eq!(wrong.into_preset().err(), Some(RulesetError::RaggedPremiums(1)));
+    let wrong = Ruleset { distribution: "a5c".to_owned(), ..ruleset };
+    assert!(matches!(wrong.into_preset(), Err(RulesetError::Distribution(_))));
+}
@@ -1,23 +1,158 @@
 
-use super::{LetterTile, Letter, Position, BOARD_SIZE};
+use std::collections::HashMap;
+
+use super::{Alphabet, LetterTile, Letter, Position, Board, UnknownCharacter, BOARD_SIZE};
+use super::solver::word_finder::TrayRemaining;
 
 /// Rules that infuence the score
 pub struct ScoreRules<Scoring: LetterScoring, Bonuses: BoardBonus> {
+    /// The score of each letter, to replace with `with_scoring` so that `letter_scores` follows
     pub scoring: Scoring,
+    /// The scores of `scoring`, read by `score::naive_score`
+    letter_scores: LetterScoreTable,
     pub bonuses: Bonuses,
-    /// The amount of bonus points in case of bingo/scrabble (aka all seven letters of the tray are played)
+    /// The amount of bonus points in case of bingo/scrabble (aka all the `rack_size` letters of the tray are played)
     pub extra_bonus: u32,
+    /// The number of tiles of a tray, all of them being played by a bingo
+    pub rack_size: usize,
+    /// The maximum multiplier of the word played in the direction of the move,
+    /// for the variants where crossing several word bonuses is capped
+    pub max_word_multiplier: Option<u32>,
+    /// How the word bonuses of the squares covered by a word are combined
+    pub premium_mode: PremiumMode,
+    /// Whether the wildcards score as the letter they stand for instead of 0
+    ///
+    /// Only the wildcards of the move and the wildcards on the board with a known letter
+    /// (in uppercase) are concerned, see `score::score_with_words`
+    pub blank_scores_as_letter: bool,
 }
 
-///
+impl<Scoring: LetterScoring, Bonuses: BoardBonus> ScoreRules<Scoring, Bonuses> {
+    /// The usual rules with these scores, bonuses and bingo bonus: the trays have
+    /// seven tiles, the word bonuses are multiplied without cap, and the wildcards score 0
+    pub fn new(scoring: Scoring, bonuses: Bonuses, extra_bonus: u32) -> Self {
+        ScoreRules {
+            letter_scores: LetterScoreTable::new(&scoring),
+            scoring,
+            bonuses,
+            extra_bonus,
+            rack_size: 7,
+            max_word_multiplier: None,
+            premium_mode: PremiumMode::Multiplicative,
+            blank_scores_as_letter: false,
+        }
+    }
+    
+    /// The same rules with other letter scores
+    pub fn with_scoring<Other: LetterScoring>(self, scoring: Other) -> ScoreRules<Other, Bonuses> {
+        ScoreRules {
+            letter_scores: LetterScoreTable::new(&scoring),
+            scoring,
+            bonuses: self.bonuses,
+            extra_bonus: self.extra_bonus,
+            rack_size: self.rack_size,
+            max_word_multiplier: self.max_word_multiplier,
+            premium_mode: self.premium_mode,
+            blank_scores_as_letter: self.blank_scores_as_letter,
+        }
+    }
+    
+    /// The same rules, borrowing the letter scores and the bonuses
+    pub fn by_ref(&self) -> ScoreRules<&Scoring, &Bonuses> {
+        ScoreRules {
+            scoring: &self.scoring,
+            letter_scores: self.letter_scores,
+            bonuses: &self.bonuses,
+            extra_bonus: self.extra_bonus,
+            rack_size: self.rack_size,
+            max_word_multiplier: self.max_word_multiplier,
+            premium_mode: self.premium_mode,
+            blank_scores_as_letter: self.blank_scores_as_letter,
+        }
+    }
+    
+    /// The scores of `scoring`, asked once when the rules were made
+    pub fn letter_scores(&self) -> &LetterScoreTable {
+        &self.letter_scores
+    }
+    
+    /// The same rules, where the bonuses are replaced by `f` of them
+    pub fn map_bonuses<Other: BoardBonus>(self, f: impl FnOnce(Bonuses) -> Other) -> ScoreRules<Scoring, Other> {
+        ScoreRules {
+            scoring: self.scoring,
+            letter_scores: self.letter_scores,
+            bonuses: f(self.bonuses),
+            extra_bonus: self.extra_bonus,
+            rack_size: self.rack_size,
+            max_word_multiplier: self.max_word_multiplier,
+            premium_mode: self.premium_mode,
+            blank_scores_as_letter: self.blank_scores_as_letter,
+        }
+    }
+}
+
+/// How the word bonuses are combined when a word covers several of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+pub enum PremiumMode {
+    /// The bonuses are multiplied (two double word squares make 4 times the word)
+    #[default]
+    Multiplicative,
+    /// The bonuses are added (two double word squares make 3 times the word)
+    Additive,
+}
+
+/// The score given to each letter
 pub trait LetterScoring: Sync {
     fn score_for(&self, letter: &LetterTile) -> u32;
+    
+    /// Whether the letter has a score, `score_for` may warn or panic when it has none
+    fn has_score_for(&self, _letter: &LetterTile) -> bool {
+        true
+    }
+}
+
+impl<Scoring: LetterScoring> LetterScoring for &Scoring {
+    fn score_for(&self, letter: &LetterTile) -> u32 {
+        (*self).score_for(letter)
+    }
+    
+    fn has_score_for(&self, letter: &LetterTile) -> bool {
+        (*self).has_score_for(letter)
+    }
+}
+
+/// Checks that all the tiles on the board and in the tray have a score
+///
+/// Returns the tiles without score otherwise
+pub fn check_scores(scoring: &impl LetterScoring, board: &Board, tray: &TrayRemaining) -> Result<(), Vec<LetterTile>> {
+    let mut unknown = vec![];
+    let tiles = board.value_table.iter_positions()
+        .filter_map(|(_, square)| square.tile().copied())
+        .chain(tray.tiles());
+    for tile in tiles {
+        if !scoring.has_score_for(&tile) && !unknown.contains(&tile) {
+            unknown.push(tile);
+        }
+    }
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(unknown)
+    }
 }
 
 pub trait BoardBonus: Sync {
     fn bonus_at(&self, position: Position) -> Bonus;
 }
 
+impl<Bonuses: BoardBonus> BoardBonus for &Bonuses {
+    fn bonus_at(&self, position: Position) -> Bonus {
+        (*self).bonus_at(position)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Bonus {
     pub letter: u32,
     pub word: u32,
@@ -62,6 +197,12 @@ impl LetterScoring for EnglishScrabbleScoring {
             },
         }
     }
+    fn has_score_for(&self, letter: &LetterTile) -> bool {
+        match letter {
+            LetterTile::Wildcard => true,
+            LetterTile::Letter(l) => Alphabet::english().contains(*l),
+        }
+    }
 }
 pub struct EnglishWordsWithFriendsScoring;
 impl LetterScoring for EnglishWordsWithFriendsScoring {
@@ -102,6 +243,12 @@ impl LetterScoring for EnglishWordsWithFriendsScoring {
             },
         }
     }
+    fn has_score_for(&self, letter: &LetterTile) -> bool {
+        match letter {
+            LetterTile::Wildcard => true,
+            LetterTile::Letter(l) => Alphabet::english().contains(*l),
+        }
+    }
 }
 
 pub struct ScrabbleBonus;
@@ -117,11 +264,7 @@ impl BoardBonus for ScrabbleBonus {
         assert_eq!(BOARD_SIZE, 15);
         
         fn fold_half(a: usize) -> usize {
-            if a >= 7 {
-                a - 7
-            } else {
-                7 - a
-            }
+            a.abs_diff(7)
         }
         
         // use the fact the bonus are symetrical from center
@@ -146,3 +289,269 @@ impl BoardBonus for ScrabbleBonus {
         }
     }
 }
+
+pub struct FrenchScrabbleScoring;
+impl LetterScoring for FrenchScrabbleScoring {
+    fn score_for(&self, letter: &LetterTile) -> u32 {
+        match letter {
+            LetterTile::Wildcard => 0,
+            LetterTile::Letter(Letter(l)) => match l {
+                b'a' => 1,
+                b'b' => 3,
+                b'c' => 3,
+                b'd' => 2,
+                b'e' => 1,
+                b'f' => 4,
+                b'g' => 2,
+                b'h' => 4,
+                b'i' => 1,
+                b'j' => 8,
+                b'k' => 10,
+                b'l' => 1,
+                b'm' => 2,
+                b'n' => 1,
+                b'o' => 1,
+                b'p' => 3,
+                b'q' => 8,
+                b'r' => 1,
+                b's' => 1,
+                b't' => 1,
+                b'u' => 1,
+                b'v' => 4,
+                b'w' => 10,
+                b'x' => 10,
+                b'y' => 10,
+                b'z' => 10,
+                _ => {
+                    log::warn!("unrecognized letter for score {}", l);
+                    0
+                },
+            },
+        }
+    }
+    fn has_score_for(&self, letter: &LetterTile) -> bool {
+        match letter {
+            LetterTile::Wildcard => true,
+            LetterTile::Letter(l) => Alphabet::english().contains(*l),
+        }
+    }
+}
+
+pub struct WordsWithFriendsBonus;
+impl BoardBonus for WordsWithFriendsBonus {
+    fn bonus_at(&self, position: Position) -> Bonus {
+        let Position { row, col } = position;
+        
+        if row > BOARD_SIZE || col > BOARD_SIZE {
+            log::error!("index for bonus is out of board");
+            return Bonus { letter: u32::MAX, word: u32::MAX };
+        }
+        
+        assert_eq!(BOARD_SIZE, 15);
+        
+        // the bonus are symetrical from center, like `ScrabbleBonus`
+        let row = row.abs_diff(7);
+        let col = col.abs_diff(7);
+        
+        match (row, col) {
+            | (7, 4) | (4, 7) => Bonus { letter: 1, word: 3 },
+            
+            | (6, 2) | (2, 6)
+            | (4, 0) | (0, 4) => Bonus { letter: 1, word: 2 },
+            
+            | (7, 1) | (1, 7)
+            | (4, 4)
+            | (2, 2) => Bonus { letter: 3, word: 1 },
+            
+            | (6, 5) | (5, 6)
+            | (5, 3) | (3, 5)
+            | (3, 1) | (1, 3) => Bonus { letter: 2, word: 1 },
+            
+            _ => Bonus { letter: 1, word: 1 }
+        }
+    }
+}
+
+/// Letter scores given by a table, like the `letter_score` of a `Ruleset`
+#[derive(Debug, Clone)]
+pub struct ArrayLetterScore {
+    /// The score of each letter, indexed by its byte
+    letters: Vec<Option<u32>>,
+    wildcard: Option<u32>,
+}
+
+impl ArrayLetterScore {
+    /// The scores of the letters (lowercase or not) and of the wildcard (`*`)
+    ///
+    /// Gives the characters that are neither ascii letters nor `*`, if any
+    pub fn new(scores: &HashMap<char, u32>) -> Result<Self, char> {
+        let mut letters = vec![None; 256];
+        let mut wildcard = None;
+        for (&c, &score) in scores {
+            match c {
+                '*' => wildcard = Some(score),
+                c if c.is_ascii_alphabetic() => letters[c.to_ascii_lowercase() as usize] = Some(score),
+                c => return Err(c),
+            }
+        }
+        Ok(ArrayLetterScore { letters, wildcard })
+    }
+}
+
+impl LetterScoring for ArrayLetterScore {
+    fn score_for(&self, letter: &LetterTile) -> u32 {
+        let score = match letter {
+            LetterTile::Wildcard => self.wildcard,
+            LetterTile::Letter(Letter(l)) => self.letters[*l as usize],
+        };
+        score.unwrap_or_else(|| {
+            log::warn!("no score for {}", letter);
+            0
+        })
+    }
+    fn has_score_for(&self, letter: &LetterTile) -> bool {
+        match letter {
+            LetterTile::Wildcard => self.wildcard.is_some(),
+            LetterTile::Letter(Letter(l)) => self.letters[*l as usize].is_some(),
+        }
+    }
+}
+
+/// The scores of a `LetterScoring`, asked once for each byte and for the wildcard
+///
+/// The tiles without score (see `LetterScoring::has_score_for`) score 0
+#[derive(Debug, Clone, Copy)]
+pub struct LetterScoreTable {
+    letters: [u32; 256],
+    wildcard: u32,
+}
+
+impl LetterScoreTable {
+    pub fn new(scoring: &impl LetterScoring) -> Self {
+        let score = |tile| if scoring.has_score_for(&tile) { scoring.score_for(&tile) } else { 0 };
+        let mut letters = [0; 256];
+        for (l, letter_score) in letters.iter_mut().enumerate() {
+            *letter_score = score(LetterTile::Letter(Letter(l as u8)));
+        }
+        LetterScoreTable { letters, wildcard: score(LetterTile::Wildcard) }
+    }
+    
+    pub fn score_for(&self, tile: &LetterTile) -> u32 {
+        match tile {
+            LetterTile::Wildcard => self.wildcard,
+            LetterTile::Letter(Letter(l)) => self.letters[*l as usize],
+        }
+    }
+}
+
+/// Bonuses given square by square, for the boards that do not have the layout of `ScrabbleBonus`
+#[derive(Debug, Clone)]
+pub struct GridBonus {
+    grid: Vec<Vec<Bonus>>,
+}
+
+impl GridBonus {
+    /// Reads the bonuses from one string per row, where a square is `.` (no bonus),
+    /// `d`, `t` or `q` (double, triple or quadruple letter), `D`, `T` or `Q` (double,
+    /// triple or quadruple word)
+    pub fn parse(rows: &[impl AsRef<str>]) -> Result<Self, UnknownCharacter> {
+        let grid = rows.iter().enumerate().map(|(line, row)| {
+            row.as_ref().bytes().enumerate().map(|(column, byte)| match byte {
+                b'.' => Ok(Bonus { letter: 1, word: 1 }),
+                b'd' => Ok(Bonus { letter: 2, word: 1 }),
+                b't' => Ok(Bonus { letter: 3, word: 1 }),
+                b'q' => Ok(Bonus { letter: 4, word: 1 }),
+                b'D' => Ok(Bonus { letter: 1, word: 2 }),
+                b'T' => Ok(Bonus { letter: 1, word: 3 }),
+                b'Q' => Ok(Bonus { letter: 1, word: 4 }),
+                byte => Err(UnknownCharacter { byte, line, column }),
+            }).collect()
+        }).collect::<Result<_, _>>()?;
+        Ok(GridBonus { grid })
+    }
+    
+    /// The number of rows and of columns (the ones of the longest row)
+    pub fn size(&self) -> (usize, usize) {
+        (self.grid.len(), self.grid.iter().map(Vec::len).max().unwrap_or(0))
+    }
+}
+
+impl BoardBonus for GridBonus {
+    fn bonus_at(&self, position: Position) -> Bonus {
+        match self.grid.get(position.row).and_then(|row| row.get(position.col)) {
+            Some(&bonus) => bonus,
+            None => {
+                log::error!("index for bonus is out of board");
+                Bonus { letter: u32::MAX, word: u32::MAX }
+            },
+        }
+    }
+}
+
+/// The bonuses of `inner`, except on the squares of `overrides`
+///
+/// Replacing a premium square with `Bonus { letter: 1, word: 1 }` tells how much
+/// it is worth (see `solver::premium_square_value`)
+#[derive(Debug, Clone)]
+pub struct MaskedBonus<Inner: BoardBonus> {
+    pub inner: Inner,
+    pub overrides: HashMap<Position, Bonus>,
+}
+
+impl<Inner: BoardBonus> BoardBonus for MaskedBonus<Inner> {
+    fn bonus_at(&self, position: Position) -> Bonus {
+        match self.overrides.get(&position) {
+            Some(&bonus) => bonus,
+            None => self.inner.bonus_at(position),
+        }
+    }
+}
+
+#[test]
+fn letter_score_table() {
+    let table = LetterScoreTable::new(&EnglishScrabbleScoring);
+    for l in b'a'..=b'z' {
+        let tile = LetterTile::Letter(Letter(l));
+        assert_eq!(table.score_for(&tile), EnglishScrabbleScoring.score_for(&tile));
+    }
+    assert_eq!(table.score_for(&LetterTile::Wildcard), 0);
+    assert_eq!(table.score_for(&LetterTile::Letter(Letter(b'.'))), 0);
+    
+    let scores = [('a', 2), ('*', 1)].iter().copied().collect();
+    let table = LetterScoreTable::new(&ArrayLetterScore::new(&scores).unwrap());
+    assert_eq!(table.score_for(&LetterTile::Letter(Letter(b'a'))), 2);
+    assert_eq!(table.score_for(&LetterTile::Letter(Letter(b'b'))), 0);
+    assert_eq!(table.score_for(&LetterTile::Wildcard), 1);
+    
+    // the cached scores follow the scoring of the rules
+    let score_rules = ScoreRules::new(EnglishScrabbleScoring, ScrabbleBonus, 50);
+    assert_eq!(score_rules.letter_scores().score_for(&LetterTile::Letter(Letter(b'a'))), 1);
+    let score_rules = score_rules.with_scoring(ArrayLetterScore::new(&scores).unwrap());
+    assert_eq!(score_rules.letter_scores().score_for(&LetterTile::Letter(Letter(b'a'))), 2);
+    assert_eq!(score_rules.by_ref().letter_scores().score_for(&LetterTile::Wildcard), 1);
+}
+
+#[test]
+fn grid_bonuses() {
+    let bonuses = GridBonus::parse(&["T.d", ".D.", "t.Q"]).unwrap();
+    assert_eq!(bonuses.size(), (3, 3));
+    assert_eq!(bonuses.bonus_at(Position { row: 0, col: 0 }), Bonus { letter: 1, word: 3 });
+    assert_eq!(bonuses.bonus_at(Position { row: 0, col: 2 }), Bonus { letter: 2, word: 1 });
+    assert_eq!(bonuses.bonus_at(Position { row: 1, col: 1 }), Bonus { letter: 1, word: 2 });
+    assert_eq!(bonuses.bonus_at(Position { row: 2, col: 0 }), Bonus { letter: 3, word: 1 });
+    assert_eq!(bonuses.bonus_at(Position { row: 2, col: 2 }), Bonus { letter: 1, word: 4 });
+    
+    assert_eq!(GridBonus::parse(&["..", ".x"]).unwrap_err(), UnknownCharacter { byte: b'x', line: 1, column: 1 });
+}
+
+#[test]
+fn masked_bonuses() {
+    let center = Position { row: 7, col: 7 };
+    let corner = Position { row: 0, col: 0 };
+    let bonuses = MaskedBonus {
+        inner: ScrabbleBonus,
+        overrides: std::iter::once((corner, Bonus { letter: 1, word: 1 })).collect(),
+    };
+    assert_eq!(bonuses.bonus_at(corner), Bonus { letter: 1, word: 1 });
+    assert_eq!(bonuses.bonus_at(center), ScrabbleBonus.bonus_at(center));
+}
@@ -3,9 +3,14 @@ pub mod word_finder;
 pub mod restrictionner;
 pub mod letter_set;
 pub mod score;
+pub mod profile;
 
 use fst::Set;
 
+use std::sync::OnceLock;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::cmp::Reverse;
+
 use typed_arena::Arena;
 use dashmap::DashMap;
 
@@ -18,8 +23,10 @@ use super::{
     Placement,
     Position,
     Move,
+    OwnedMove,
+    BOARD_SIZE,
 };
-use super::{Board, Table};
+use super::{Alphabet, Board, Table};
 
 use letter_set::LetterSet;
 use word_finder::TrayRemaining;
@@ -35,32 +42,62 @@ pub enum RestrictedSquare {
     Filled(LetterTile),
 }
 
+/// A line of restricted squares, read square by square
+///
+/// This allows the restrictions to be computed only when they are first read (see `LazyConstrainedBoard`)
+pub trait RestrictedLine: Copy {
+    /// The square `i` of the line, None if it is out of the board
+    fn square(&self, i: usize) -> Option<RestrictedSquare>;
+    /// The same line, starting at its square `i`
+    fn sub_line(self, i: usize) -> Self;
+}
+
+impl RestrictedLine for &[RestrictedSquare] {
+    fn square(&self, i: usize) -> Option<RestrictedSquare> {
+        self.get(i).copied()
+    }
+    fn sub_line(self, i: usize) -> Self {
+        &self[i..]
+    }
+}
+
 #[derive(Clone)]
 pub struct ConstrainedBoard {
     /// The direction in which the constraints have been collected (perp of what they will be used for)
     dir: Direction,
+    /// `table[i][j]` is the square `j` of the line `i` in the direction perp of `dir`
     table: Vec<Vec<RestrictedSquare>>,
+    center: Position,
+    /// Whether the words must cover the center, guessed from the board if not present
+    first_move: Option<bool>,
 }
 
 impl ConstrainedBoard {
-    pub fn build(board_table: &Table<Square>, dir: Direction, dictionary: &Set<impl AsRef<[u8]>>) -> Self {
-        let mut table = vec![vec![RestrictedSquare::Empty(LetterSet::empty()); 15]; 15];
+    /// The restrictions of each square from the tiles next to it in the direction `dir`
+    ///
+    /// `evaluate` builds one board per direction. Both could be filled in a single sweep
+    /// over the squares, but each restriction is its own search in the dictionary, which
+    /// is almost all the time of the build, so this would not be faster
+    pub fn build(board_table: &Table<Square>, dir: Direction, dictionary: &Set<impl AsRef<[u8]>>, exclude: &HashSet<String>) -> Self {
+        let line_len = board_table.len_in(dir);
+        let n_lines = board_table.len_in(dir.perp());
+        let mut table = vec![vec![RestrictedSquare::Empty(LetterSet::empty()); n_lines]; line_len];
         
         let mut start = Placement(Position { row: 0, col: 0 }, dir);
         
-        for i in 0..15 {
-            let mut buf = [Square::Empty; 15];
-            let mut head = start.clone();
-            for j in 0..15 {
-                buf[j] = board_table.get(head.0).unwrap().clone();
+        for i in 0..n_lines {
+            let mut buf = vec![Square::Empty; line_len];
+            let mut head = start;
+            for square in buf.iter_mut() {
+                *square = *board_table.get(head.0).unwrap();
                 head = head.next();
             }
             
-            let mut bur_restr = [RestrictedSquare::Empty(LetterSet::empty()); 15];
-            restrictionner::find_restrictions(&buf[..], &mut bur_restr[..], dictionary);
+            let mut bur_restr = vec![RestrictedSquare::Empty(LetterSet::empty()); line_len];
+            restrictionner::find_restrictions(&buf[..], &mut bur_restr[..], dictionary, exclude);
             
-            for j in 0..15 {
-                table[j][i] = bur_restr[j];
+            for (row, &restr) in table.iter_mut().zip(bur_restr.iter()) {
+                row[i] = restr;
             }
             
             start = start.perp().next().perp();
@@ -69,80 +106,339 @@ impl ConstrainedBoard {
         Self {
             table,
             dir,
+            center: board_table.center(),
+            first_move: None,
         }
     }
     
+    /// Tells whether the move is the first of the game, which must cover the center
+    /// (see `Rules::first_move`), instead of guessing it from an empty board
+    pub fn with_first_move(self, first_move: Option<bool>) -> Self {
+        Self { first_move, ..self }
+    }
+    
     fn is_empty(&self) -> bool {
-        for i in 0..15 {
-            for j in 0..15 {
-                if let RestrictedSquare::Filled(_) = self.table[i][j] {
-                    return false
-                }
-            }
-        }
-        true
+        self.table.iter().flatten().all(|square| !matches!(square, RestrictedSquare::Filled(_)))
+    }
+    
+    fn is_first_move(&self) -> bool {
+        self.first_move.unwrap_or_else(|| self.is_empty())
+    }
+    
+    /// The placements where a word can start, with the slice of the line from there
+    /// and the minimum length of the word (see `Rules::require_attachment`)
+    pub fn explore(&self, require_attachment: bool) -> impl Iterator<Item=(
+        Placement,
+        &[RestrictedSquare],
+        usize,
+    )> {
+        (0..self.table.len()).flat_map(move |i| self.explore_line(i, require_attachment))
     }
     
-    pub fn explore(&self) -> impl Iterator<Item=(
+    /// Same as `explore`, for the placements on a single line
+    ///
+    /// The line `index` is a row if the constraints have been collected vertically, a column otherwise
+    pub fn explore_line(&self, index: usize, require_attachment: bool) -> impl Iterator<Item=(
         Placement,
         &[RestrictedSquare],
         usize,
     )> {
+        let line_slice = &self.table[index][..];
         let mut line = Placement(Position { row: 0, col: 0 }, self.dir.perp());
-        let is_empty = self.is_empty();
-        std::iter::from_fn(move || {
-            if line.0[self.dir] >= 15 {
-                return None
+        line.0[self.dir] = index;
+        explore_line_with(
+            line,
+            move |j| matches!(line_slice[j], RestrictedSquare::Filled(_)),
+            move |j| require_attachment && matches!(line_slice[j], RestrictedSquare::Empty(letter_set) if letter_set.is_any()),
+            line_slice.len(),
+            if self.is_first_move() { Some(self.center) } else { None },
+        ).map(move |(place, min_len)| (
+            place,
+            &line_slice[place.0[self.dir.perp()]..],
+            min_len,
+        ))
+    }
+}
+
+/// The placements where a word can start on the line beginning at `line`,
+/// with the minimum length of a word starting there to be attached
+///
+/// `is_filled(i)` tells whether there is a tile on the square `i` of the line,
+/// and `is_free(i)` whether it is empty without any perpendicular word.
+/// On an empty board, the words must cover the `center`. When no square is
+/// free, any word of two letters or more is attached
+fn explore_line_with(
+    line: Placement,
+    is_filled: impl Fn(usize) -> bool,
+    is_free: impl Fn(usize) -> bool,
+    len: usize,
+    center: Option<Position>,
+) -> impl Iterator<Item=(Placement, usize)> {
+    let dir = line.1;
+    let mut head = line;
+    std::iter::from_fn(move || {
+        while head.0[dir] < len {
+            // skip the square just after a tile
+            if head.0[dir] > 0 && is_filled(head.0[dir] - 1) {
+                head = head.next();
+            } else {
+                break
             }
-            let mut head = line.clone();
-            let line_slice = &self.table[line.0[self.dir]][..];
-            line = line.perp().next().perp();
-            Some(std::iter::from_fn(move || {
-                while head.0[self.dir.perp()] < 15 {
-                    // skip the square just after a tile
-                    match line_slice.get(head.back().0[self.dir.perp()]) {
-                        None | Some(RestrictedSquare::Empty(_)) => break,
-                        Some(RestrictedSquare::Filled(_)) => {
-                            head = head.next();
-                            continue
-                        },
-                    }
-                }
-                
-                if head.0[self.dir.perp()] >= 15 {
-                    return None
-                }
-                
-                let sub_slice = &line_slice[head.0[self.dir.perp()]..];
-                let place = head.clone();
+        }
+        
+        if head.0[dir] >= len {
+            return None
+        }
+        
+        let place = head;
+        head = head.next();
+        
+        // find minimum length to be attached: first square that is filled or that have constraints (some perpendicular word)
+        let mut end = place;
+        while end.0[dir] < len {
+            if center == Some(end.0) {
+                break
+            }
+            if is_free(end.0[dir]) {
+                end = end.next();
+            } else {
+                break
+            }
+        }
+        
+        if end.0[dir] == len { // The line is empty
+            return None
+        }
+        
+        Some((
+            place,
+            (end.0[dir] - place.0[dir] + 1).max(2),
+        ))
+    })
+}
+
+/// The empty squares next to a tile (or the center on an empty board), in the
+/// order of the rows
+///
+/// Each move places a tile on at least one of them
+pub fn anchor_squares(board: &Board) -> Vec<Position> {
+    let table = &board.letter_table;
+    let is_filled = |pos| matches!(table.get(pos), Some(Square::Filled(_)));
+    
+    let anchors = table.iter_positions()
+    .filter(|(_, square)| square.tile().is_none())
+    .map(|(pos, _)| pos)
+    .filter(|&pos| [Direction::Horizontal, Direction::Vertical].iter().any(|&dir| {
+        let place = Placement(pos, dir);
+        place.try_back(table.len_in(dir)).is_some_and(|back| is_filled(back.0))
+        || place.try_next(table.len_in(dir)).is_some_and(|next| is_filled(next.0))
+    }))
+    .collect::<Vec<_>>();
+    
+    if anchors.is_empty() && table.iter_positions().all(|(_, square)| square.tile().is_none()) {
+        vec![table.center()]
+    } else {
+        anchors
+    }
+}
+
+/// The letters that a move in the direction `dir` can place on each square of the
+/// board, because of the words they form in the perpendicular direction
+///
+/// The filled squares allow no letter
+pub fn cross_checks(
+    board: &Board, dir: Direction,
+    dictionary: &Set<impl AsRef<[u8]>>, exclude: &HashSet<String>,
+) -> Table<LetterSet> {
+    let table = &board.letter_table;
+    let constrained = ConstrainedBoard::build(table, dir.perp(), dictionary, exclude);
+    
+    let mut checks = Table::with_size(table.len_in(Direction::Vertical), table.len_in(Direction::Horizontal), LetterSet::empty());
+    for (k, line) in constrained.table.iter().enumerate() {
+        for (i, restr) in line.iter().enumerate() {
+            if let RestrictedSquare::Empty(letter_set) = restr {
+                let mut pos = Position { row: 0, col: 0 };
+                pos[constrained.dir] = k;
+                pos[constrained.dir.perp()] = i;
+                checks.set(pos, *letter_set);
+            }
+        }
+    }
+    checks
+}
+
+/// The cross-checks (see `cross_checks`) as a grid with one line per row, for debugging
+///
+/// A square is `.` when it allows any letter, `-` when it allows none (or is
+/// filled), and the allowed letters otherwise
+pub fn render_cross_checks(checks: &Table<LetterSet>) -> String {
+    render_cross_checks_with(checks, |_| false)
+}
+
+/// Same as `render_cross_checks`, where the filled squares of the `board` are `#`
+pub fn render_board_cross_checks(board: &Board, checks: &Table<LetterSet>) -> String {
+    render_cross_checks_with(checks, |pos| board.letter_table.get(pos).and_then(Square::tile).is_some())
+}
+
+fn render_cross_checks_with(checks: &Table<LetterSet>, is_filled: impl Fn(Position) -> bool) -> String {
+    let cells = checks.iter_positions().map(|(pos, letter_set)| {
+        let cell = if is_filled(pos) {
+            "#".to_owned()
+        } else if letter_set.is_any() {
+            ".".to_owned()
+        } else if letter_set.is_empty() {
+            "-".to_owned()
+        } else {
+            (0..=255u8).filter(|&l| letter_set.contains(Letter(l))).map(char::from).collect()
+        };
+        (pos, cell)
+    }).collect::<Vec<_>>();
+    let width = cells.iter().map(|(_, cell)| cell.len()).max().unwrap_or(1);
+    
+    let mut rendered = String::new();
+    for row in cells.chunk_by(|(a, _), (b, _)| a.row == b.row) {
+        let line = row.iter().map(|(_, cell)| format!("{:<width$}", cell, width = width)).collect::<Vec<_>>().join(" ");
+        rendered.push_str(line.trim_end());
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// A `ConstrainedBoard` where the restriction of a square is only computed
+/// (and then kept) the first time it is read
+///
+/// This avoids looking up the dictionary for the squares that no word reaches,
+/// which is a better tradeoff for boards with few tiles and big dictionaries
+pub struct LazyConstrainedBoard<'d, D: AsRef<[u8]>> {
+    /// The direction in which the constraints are collected (perp of what they will be used for)
+    dir: Direction,
+    /// The squares of the board, `lines[i][j]` being the square `j` in the direction `dir` of the line `i`
+    lines: Vec<Vec<Square>>,
+    /// The restrictions computed so far, indexed like `ConstrainedBoard`'s table
+    table: Vec<Vec<OnceLock<RestrictedSquare>>>,
+    dictionary: &'d Set<D>,
+    exclude: &'d HashSet<String>,
+    center: Position,
+    /// Whether the words must cover the center, guessed from the board if not present
+    first_move: Option<bool>,
+}
+
+impl<'d, D: AsRef<[u8]>> LazyConstrainedBoard<'d, D> {
+    pub fn new(board_table: &Table<Square>, dir: Direction, dictionary: &'d Set<D>, exclude: &'d HashSet<String>) -> Self {
+        let line_len = board_table.len_in(dir);
+        let n_lines = board_table.len_in(dir.perp());
+        let mut lines = vec![vec![Square::Empty; line_len]; n_lines];
+        
+        let mut start = Placement(Position { row: 0, col: 0 }, dir);
+        
+        for line in lines.iter_mut() {
+            let mut head = start;
+            for square in line.iter_mut() {
+                *square = *board_table.get(head.0).unwrap();
                 head = head.next();
-                
-                // find minimum length to be attached: first square that is filled or that have constraints (some perpendicular word)
-                let mut end = place.clone();
-                while end.0[self.dir.perp()] < 15 {
-                    if is_empty && end.0 == (Position { row: 7, col: 7 }) {
-                        break
-                    }
-                    match line_slice[end.0[self.dir.perp()]] {
-                        RestrictedSquare::Empty(letter_set) if letter_set.is_any() => {
-                            end = end.next();
-                            continue
-                        },
-                        _ => break
-                    }
-                }
-                
-                if end.0[self.dir.perp()] == 15 { // The line is empty
-                    return None
-                }
-                
-                Some((
-                    place,
-                    sub_slice,
-                    (end.0[self.dir.perp()] - place.0[self.dir.perp()] + 1).max(2),
-                ))
-            }))
-        }).flatten()
+            }
+            start = start.perp().next().perp();
+        }
+        
+        Self {
+            dir,
+            lines,
+            table: (0..line_len).map(|_| (0..n_lines).map(|_| OnceLock::new()).collect()).collect(),
+            dictionary,
+            exclude,
+            center: board_table.center(),
+            first_move: None,
+        }
+    }
+    
+    /// Same as `ConstrainedBoard::with_first_move`
+    pub fn with_first_move(self, first_move: Option<bool>) -> Self {
+        Self { first_move, ..self }
+    }
+    
+    /// The restriction of the square `j` of the line `i`
+    fn restriction(&self, i: usize, j: usize) -> RestrictedSquare {
+        *self.table[i][j].get_or_init(|| {
+            restrictionner::find_restriction(&self.lines[j], i, self.dictionary, self.exclude)
+        })
+    }
+    
+    fn is_filled(&self, i: usize, j: usize) -> bool {
+        self.lines[j][i].tile().is_some()
+    }
+    
+    /// Whether the square is empty without any perpendicular word, known without the dictionary
+    fn is_free(&self, i: usize, j: usize) -> bool {
+        let line = &self.lines[j];
+        line[i].tile().is_none()
+        && (i == 0 || line[i - 1].tile().is_none())
+        && line.get(i + 1).and_then(Square::tile).is_none()
+    }
+    
+    fn is_empty(&self) -> bool {
+        self.lines.iter().flatten().all(|square| square.tile().is_none())
+    }
+    
+    fn is_first_move(&self) -> bool {
+        self.first_move.unwrap_or_else(|| self.is_empty())
+    }
+    
+    /// Same as `ConstrainedBoard::explore`, without computing any restriction
+    pub fn explore(&self, require_attachment: bool) -> impl Iterator<Item=(
+        Placement,
+        LazyLine<'_, 'd, D>,
+        usize,
+    )> {
+        let center = if self.is_first_move() { Some(self.center) } else { None };
+        (0..self.table.len()).flat_map(move |i| {
+            let mut line = Placement(Position { row: 0, col: 0 }, self.dir.perp());
+            line.0[self.dir] = i;
+            explore_line_with(
+                line,
+                move |j| self.is_filled(i, j),
+                move |j| require_attachment && self.is_free(i, j),
+                self.lines.len(),
+                center,
+            ).map(move |(place, min_len)| (
+                place,
+                LazyLine { board: self, index: i, start: place.0[self.dir.perp()] },
+                min_len,
+            ))
+        })
+    }
+}
+
+/// A line of a `LazyConstrainedBoard`
+pub struct LazyLine<'b, 'd, D: AsRef<[u8]>> {
+    board: &'b LazyConstrainedBoard<'d, D>,
+    index: usize,
+    start: usize,
+}
+
+impl<'b, 'd, D: AsRef<[u8]>> Clone for LazyLine<'b, 'd, D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'b, 'd, D: AsRef<[u8]>> Copy for LazyLine<'b, 'd, D> {}
+
+impl<'b, 'd, D: AsRef<[u8]>> std::fmt::Debug for LazyLine<'b, 'd, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "LazyLine({}, from {})", self.index, self.start)
+    }
+}
+
+impl<'b, 'd, D: AsRef<[u8]>> RestrictedLine for LazyLine<'b, 'd, D> {
+    fn square(&self, i: usize) -> Option<RestrictedSquare> {
+        let j = self.start + i;
+        if j < self.board.lines.len() {
+            Some(self.board.restriction(self.index, j))
+        } else {
+            None
+        }
+    }
+    fn sub_line(self, i: usize) -> Self {
+        Self { start: self.start + i, ..self }
     }
 }
 
@@ -161,22 +457,23 @@ impl ConstrainedBoard {
 /// A word can be played on the same place with a different assigment of wildcards.
 /// As using more wildcards will only gives a lower score, we only generate moves
 /// with the minimum number of wildcards required for the word (by using them as letters we don't have).
+#[allow(clippy::too_many_arguments)]
 pub fn generate_moves_for_word<'a>(
     current_place: Placement,
     first: Option<(Placement, LetterTile, usize)>, // usize is n_steps since last
     others: &mut Vec<(usize, LetterTile)>,
-    sub_slice: &[RestrictedSquare], word: &[u8],
+    sub_slice: impl RestrictedLine, word: &[u8],
     wildcards_intersection: &[bool], wildcards_missing: &[u8; 256],
     moves: &mut Vec<Move<'a>>, arenas_mov: &'a Arena<(usize, LetterTile)>,
 ) {
-    if word.len() == 0 {
+    if word.is_empty() {
         if wildcards_missing.iter().any(|&c| c != 0) {
             // there are wilcards that have not been played that should have
             return
         }
         // base case
         let (first_place, first_letter, _) = first.unwrap();
-        if others.len() == 0 {
+        if others.is_empty() {
             moves.push(Move::SingleLetter(first_place.0, first_letter));
         } else {
             moves.push(Move::MultiLetters(first_place, first_letter, arenas_mov.alloc_extend(others.iter().cloned())));
@@ -184,14 +481,14 @@ pub fn generate_moves_for_word<'a>(
     } else {
         // move to next
         let next_place = current_place.next();
-        let next_sub_slice = &sub_slice[1..];
+        let next_sub_slice = sub_slice.sub_line(1);
         let next_word = &word[1..];
         let next_wildcards_intersection = &wildcards_intersection[1..];
         
-        if let RestrictedSquare::Empty(_) = sub_slice[0] {
+        if let Some(RestrictedSquare::Empty(_)) = sub_slice.square(0) {
             if !wildcards_intersection[0] && wildcards_missing[word[0] as usize] > 0 {
                 // extra path for using the wildcards
-                let mut wildcards_missing_new = wildcards_missing.clone();
+                let mut wildcards_missing_new = *wildcards_missing;
                 wildcards_missing_new[word[0] as usize] -= 1;
                 
                 let (first, was_first) = if let Some((p_first, l_first, n_step)) = first {
@@ -257,8 +554,16 @@ pub mod arenas {
     use typed_arena::Arena;
     use std::sync::Mutex;
     
+    // the arenas are boxed so that they don't move when the vec grows
+    #[allow(clippy::vec_box)]
     pub struct Arenas<T>(Mutex<Vec<Box<Arena<T>>>>);
     
+    impl<T> Default for Arenas<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+    
     impl<T> Arenas<T> {
         pub fn new() -> Arenas<T> {
             Arenas(Mutex::new(vec![]))
@@ -271,7 +576,7 @@ pub mod arenas {
             let a = Box::new(Arena::new());
             let mut inner = self.0.lock().unwrap();
             inner.push(a);
-            let b: &Arena<_> = &*inner.last().unwrap();
+            let b: &Arena<_> = inner.last().unwrap();
             
             // extends lifetime from the lifetime of `inner` to the lifetime of what is returned by the function (`self`)
             unsafe { (b as *const Arena<T>).as_ref().unwrap() }
@@ -312,8 +617,178 @@ impl<'a> std::fmt::Debug for StrList<'a> {
 }
 
 pub struct EvaluationResult<'a> {
+    /// The words of every move found, even the ones below `min_score` of the rules
     pub words: dashmap::ReadOnlyView<Move<'a>, &'a StrList<'a>>,
     pub score: Vec<(Move<'a>, u32)>,
+    /// Whether some moves were dropped because of the limit (see `evaluate_with_limit`)
+    pub truncated: bool,
+    /// What the automaton did while browsing the dictionary
+    #[cfg(feature = "profile")]
+    pub stats: profile::EvaluationStats,
+}
+
+impl<'a> EvaluationResult<'a> {
+    /// Copies the result out of the arenas
+    pub fn into_owned(self) -> OwnedEvaluationResult {
+        let words = self.words.iter().map(|(a_move, list)| {
+            let mut words = list.to_vec().into_iter().map(str::to_owned).collect::<Vec<_>>();
+            words.sort_unstable();
+            (OwnedMove::from(a_move), words)
+        }).collect();
+        let mut score = self.score.iter().map(|(a_move, score)| (OwnedMove::from(a_move), *score)).collect::<Vec<_>>();
+        // moves with the same score are in no particular order after the parallel sort
+        score.sort_unstable_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+        OwnedEvaluationResult {
+            words,
+            score,
+            truncated: self.truncated,
+            #[cfg(feature = "profile")]
+            stats: self.stats,
+        }
+    }
+    
+    /// The moves of both results, with the words of both for each move and the higher score
+    ///
+    /// This is for results of the same board with different dictionaries. Both results
+    /// borrow from their arenas, so the merged one lives as long as the shortest lived of
+    /// them, and the lists holding the words of both results are allocated in `arenas_str_list`
+    pub fn merge(self, other: Self, arenas_str_list: &'a Arenas<StrList<'a>>) -> Self {
+        let arena_str_list = arenas_str_list.new_arena();
+        
+        let words: DashMap<Move<'a>, &'a StrList<'a>> = DashMap::new();
+        for (a_move, &list) in self.words.iter() {
+            words.insert(a_move.clone(), list);
+        }
+        for (a_move, &list) in other.words.iter() {
+            let mut entry = words.entry(a_move.clone()).or_insert(&StrList::EMPTY_LIST);
+            let known = entry.value().to_vec();
+            for word in list.to_vec() {
+                if !known.contains(&word) {
+                    *entry.value_mut() = arena_str_list.alloc(StrList::Elem(word, entry.value()));
+                }
+            }
+        }
+        
+        let mut best: HashMap<Move<'a>, u32> = HashMap::new();
+        for (a_move, score) in self.score.into_iter().chain(other.score) {
+            let best_score = best.entry(a_move).or_insert(score);
+            *best_score = (*best_score).max(score);
+        }
+        let mut score = best.into_iter().collect::<Vec<_>>();
+        score.sort_unstable_by_key(|(_, s)| *s);
+        
+        EvaluationResult {
+            words: words.into_read_only(),
+            score,
+            truncated: self.truncated || other.truncated,
+            #[cfg(feature = "profile")]
+            stats: profile::EvaluationStats {
+                transitions: self.stats.transitions + other.stats.transitions,
+                can_match_checks: self.stats.can_match_checks + other.stats.can_match_checks,
+                matches: self.stats.matches + other.stats.matches,
+            },
+        }
+    }
+    
+    /// The move with the `k`-th best score, `nth_best(1)` being the best move
+    ///
+    /// None if `k` is 0 or if there are fewer moves. The moves with the same score
+    /// are in no particular order
+    pub fn nth_best(&self, k: usize) -> Option<&(Move<'a>, u32)> {
+        k.checked_sub(1).and_then(|i| self.score.iter().rev().nth(i))
+    }
+    
+    /// The moves with their score, and whether they are still valid with the `strict` dictionary
+    ///
+    /// A move is safe when one of its words is in `strict`, as well as the words it
+    /// forms in the perpendicular direction on the `board` it was found for (where
+    /// wildcards accept any letter). The returned vec is sorted by score like `score`
+    pub fn mark_safe(&self, board: &Board, strict: &Set<impl AsRef<[u8]>>) -> Vec<(Move<'a>, u32, bool)> {
+        self.score.iter().map(|(a_move, score)| {
+            let main_word_safe = self.words.get(a_move)
+                .is_some_and(|list| list.to_vec().iter().any(|word| strict.contains(word)));
+            let safe = main_word_safe && crate::cross_word_failures(board, a_move, strict).is_empty();
+            (a_move.clone(), *score, safe)
+        }).collect()
+    }
+    
+    /// The score of the move if it was found, the move can be built elsewhere (see `Move::canonical`)
+    pub fn contains(&self, a_move: &Move) -> Option<u32> {
+        let a_move = a_move.canonical();
+        self.score.iter().find(|(found, _)| *found == a_move).map(|(_, score)| *score)
+    }
+    
+    /// The moves grouped by score and words, with the number of moves in each group
+    ///
+    /// Only one move is kept for the plays that cannot be told apart in a list of
+    /// words (like the same word played at several places on a symmetric board).
+    /// The words are the ones played in the direction of the move, and the groups
+    /// are sorted by score like `score`
+    pub fn dedup_equivalent(&self) -> Vec<(Move<'a>, u32, usize)> {
+        let mut groups: Vec<(Move<'a>, u32, usize)> = vec![];
+        let mut group_of: HashMap<(Vec<&str>, u32), usize> = HashMap::new();
+        for (a_move, score) in &self.score {
+            let mut words = self.words.get(a_move).map(|list| list.to_vec()).unwrap_or_default();
+            words.sort_unstable();
+            words.dedup();
+            match group_of.entry((words, *score)) {
+                std::collections::hash_map::Entry::Occupied(entry) => groups[*entry.get()].2 += 1,
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(groups.len());
+                    groups.push((a_move.clone(), *score, 1));
+                },
+            }
+        }
+        groups
+    }
+    
+    /// The moves with the best score for each length of the word played in
+    /// the direction of the move, `counts` giving how many moves of each length are kept
+    ///
+    /// The returned vec is sorted by score like `score`
+    pub fn sample_by_length(&self, counts: &BTreeMap<usize, usize>) -> Vec<(Move<'a>, u32)> {
+        let mut left = counts.clone();
+        let mut sample = self.score.iter().rev()
+            .filter(|(a_move, _)| {
+                let len = match self.words.get(a_move).and_then(|list| list.to_vec().first().map(|word| word.len())) {
+                    Some(len) => len,
+                    None => return false,
+                };
+                match left.get_mut(&len) {
+                    Some(n) if *n > 0 => {
+                        *n -= 1;
+                        true
+                    },
+                    _ => false,
+                }
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        sample.reverse();
+        sample
+    }
+}
+
+/// An `EvaluationResult` that does not borrow from the arenas
+///
+/// The moves are in a deterministic order: sorted by score, then by move,
+/// and so are the words of each move
+#[derive(Clone, PartialEq, Eq)]
+pub struct OwnedEvaluationResult {
+    pub words: HashMap<OwnedMove, Vec<String>>,
+    pub score: Vec<(OwnedMove, u32)>,
+    pub truncated: bool,
+    #[cfg(feature = "profile")]
+    pub stats: profile::EvaluationStats,
+}
+
+impl std::fmt::Debug for OwnedEvaluationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (a_move, score) in &self.score {
+            writeln!(f, "{:>3}: {:?} {:?}", score, a_move, self.words[a_move])?;
+        }
+        Ok(())
+    }
 }
 
 /// Evaluate all the words that can be played on the board, and the score with the associated move
@@ -326,78 +801,268 @@ pub fn evaluate<'a>(
     tray: &TrayRemaining, board: &Board,
     rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
 ) -> EvaluationResult<'a> {
-    use fst::{IntoStreamer, Streamer};
-    use word_finder::ScrabbleAutomata;
-    use rayon::prelude::*;
+    evaluate_with_limit(
+        arenas_str, arenas_str_list, arenas_mov,
+        tray, board,
+        rules, usize::MAX,
+    )
+}
+
+/// Same as `evaluate`, but at most `max_moves` moves are kept, to bound the memory used
+///
+/// Once the limit is reached, a move found afterwards evicts the kept move with the
+/// lowest score if it scores more, and the result is marked as `truncated`. The
+/// moves with the same score are kept by their order, so the result does not depend
+/// on the parallel search
+pub fn evaluate_with_limit<'a>(
+    arenas_str: &'a Arenas<u8>,
+    arenas_str_list: &'a Arenas<StrList<'a>>,
+    arenas_mov: &'a Arenas<(usize, LetterTile)>,
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    max_moves: usize,
+) -> EvaluationResult<'a> {
+    evaluate_with_granularity(
+        arenas_str, arenas_str_list, arenas_mov,
+        tray, board,
+        rules, max_moves, DEFAULT_PLACEMENTS_PER_TASK,
+    )
+}
+
+/// The default number of placements explored by each parallel task, which lets rayon split the work as it wants
+pub const DEFAULT_PLACEMENTS_PER_TASK: usize = 1;
+
+/// Same as `evaluate_with_limit`, where each parallel task explores at least
+/// `placements_per_task` placements (where a word can start)
+///
+/// Bigger tasks have less overhead on boards with few tiles, smaller tasks share
+/// the work better when each placement takes long (with big dictionaries)
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_with_granularity<'a>(
+    arenas_str: &'a Arenas<u8>,
+    arenas_str_list: &'a Arenas<StrList<'a>>,
+    arenas_mov: &'a Arenas<(usize, LetterTile)>,
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    max_moves: usize,
+    placements_per_task: usize,
+) -> EvaluationResult<'a> {
+    evaluate_with_rules(
+        arenas_str, arenas_str_list, arenas_mov,
+        tray, board,
+        &rules, max_moves, placements_per_task,
+    )
+}
+
+/// Same as `evaluate_with_granularity`, with borrowed rules
+#[allow(clippy::too_many_arguments)]
+fn evaluate_with_rules<'a>(
+    arenas_str: &'a Arenas<u8>,
+    arenas_str_list: &'a Arenas<StrList<'a>>,
+    arenas_mov: &'a Arenas<(usize, LetterTile)>,
+    tray: &TrayRemaining, board: &Board,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    max_moves: usize,
+    placements_per_task: usize,
+) -> EvaluationResult<'a> {
+    let dictionary = rules.cross_dictionary();
+    
+    let prepared_h = ConstrainedBoard::build(&board.letter_table, Direction::Vertical, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    let prepared_v = ConstrainedBoard::build(&board.letter_table, Direction::Horizontal, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    
+    let placements = prepared_v.explore(rules.require_attachment).chain(prepared_h.explore(rules.require_attachment)).collect::<Vec<_>>();
+    
+    evaluate_placements(
+        arenas_str, arenas_str_list, arenas_mov,
+        tray, board,
+        rules, &rules.dictionary, placements, max_moves, placements_per_task,
+    )
+}
+
+/// The rules and dictionary kept between solves, for a program solving many boards
+///
+/// The rules can be changed between two solves, or for a single solve with
+/// `RulesOverrides`. The arenas are not kept: `typed_arena` only frees its memory
+/// when an arena is dropped, and the lists of words borrow from the arena of the
+/// words, so both could not be owned by the context. The arenas of a solve are
+/// dropped as soon as its result is copied out of them
+pub struct SolverContext<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]>> {
+    pub rules: Rules<Scoring, Bonuses, DictionaryStorage>,
+    /// The maximum number of moves kept by a solve (see `evaluate_with_limit`)
+    pub max_moves: usize,
+    /// The number of placements explored by each parallel task (see `evaluate_with_granularity`)
+    pub placements_per_task: usize,
+}
+
+/// The rules of a `SolverContext` that are replaced for a single solve, the
+/// ones that are `None` are left as they are
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RulesOverrides {
+    pub wildcards_have_multi_meaning: Option<bool>,
+    pub min_main_word_len: Option<usize>,
+    pub must_play: Option<Vec<Letter>>,
+    pub bingos_only: Option<bool>,
+    pub max_tiles_placed: Option<Option<usize>>,
+    pub min_score: Option<u32>,
+    pub exclude: Option<HashSet<String>>,
+    pub require_attachment: Option<bool>,
+    pub region: Option<Option<(Position, Position)>>,
+    pub first_move: Option<Option<bool>>,
+}
+
+impl<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]> + Sync> SolverContext<Scoring, Bonuses, DictionaryStorage> {
+    pub fn new(rules: Rules<Scoring, Bonuses, DictionaryStorage>) -> Self {
+        Self {
+            rules,
+            max_moves: usize::MAX,
+            placements_per_task: DEFAULT_PLACEMENTS_PER_TASK,
+        }
+    }
+    
+    /// Same as `evaluate_with_limit` with the rules of the context, where `rules_overrides` are applied
+    pub fn solve(&self, tray: &TrayRemaining, board: &Board, rules_overrides: &RulesOverrides) -> OwnedEvaluationResult {
+        let arenas_str: Arenas<u8> = Arenas::new();
+        let arenas_str_list: Arenas<StrList> = Arenas::new();
+        let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+        
+        evaluate_with_rules(
+            &arenas_str, &arenas_str_list, &arenas_mov,
+            tray, board,
+            &self.overridden_rules(rules_overrides), self.max_moves, self.placements_per_task,
+        ).into_owned()
+    }
+    
+    /// The rules of the context with the overrides, borrowing the scores and the dictionaries
+    fn overridden_rules(&self, overrides: &RulesOverrides) -> Rules<&Scoring, &Bonuses, &[u8]> {
+        let rules = &self.rules;
+        // the dictionaries are only borrowed, their bytes were already checked when they were built
+        fn borrow<D: AsRef<[u8]>>(dictionary: &Set<D>) -> Set<&[u8]> {
+            Set::new(dictionary.as_fst().as_bytes()).unwrap()
+        }
+        Rules {
+            score_rules: rules.score_rules.by_ref(),
+            wildcards_have_multi_meaning: overrides.wildcards_have_multi_meaning.unwrap_or(rules.wildcards_have_multi_meaning),
+            min_main_word_len: overrides.min_main_word_len.unwrap_or(rules.min_main_word_len),
+            must_play: overrides.must_play.clone().unwrap_or_else(|| rules.must_play.clone()),
+            bingos_only: overrides.bingos_only.unwrap_or(rules.bingos_only),
+            max_tiles_placed: overrides.max_tiles_placed.unwrap_or(rules.max_tiles_placed),
+            min_score: overrides.min_score.unwrap_or(rules.min_score),
+            dictionary: borrow(&rules.dictionary),
+            cross_dictionary: rules.cross_dictionary.as_ref().map(borrow),
+            exclude: overrides.exclude.clone().unwrap_or_else(|| rules.exclude.clone()),
+            require_attachment: overrides.require_attachment.unwrap_or(rules.require_attachment),
+            region: overrides.region.unwrap_or(rules.region),
+            first_move: overrides.first_move.unwrap_or(rules.first_move),
+        }
+    }
+}
+
+/// Same as `evaluate`, but the restrictions coming from the perpendicular words
+/// are only computed when needed (see `LazyConstrainedBoard`)
+pub fn evaluate_lazy<'a>(
+    arenas_str: &'a Arenas<u8>,
+    arenas_str_list: &'a Arenas<StrList<'a>>,
+    arenas_mov: &'a Arenas<(usize, LetterTile)>,
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> EvaluationResult<'a> {
+    let dictionary = rules.cross_dictionary();
+    
+    let prepared_h = LazyConstrainedBoard::new(&board.letter_table, Direction::Vertical, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    let prepared_v = LazyConstrainedBoard::new(&board.letter_table, Direction::Horizontal, dictionary, &rules.exclude).with_first_move(rules.first_move);
     
-    let dictionary = &rules.dictionary;
+    let placements = prepared_v.explore(rules.require_attachment).chain(prepared_h.explore(rules.require_attachment)).collect::<Vec<_>>();
     
-    let prepared_h = ConstrainedBoard::build(&board.letter_table, Direction::Vertical, &dictionary);
-    let prepared_v = ConstrainedBoard::build(&board.letter_table, Direction::Horizontal, &dictionary);
+    evaluate_placements(
+        arenas_str, arenas_str_list, arenas_mov,
+        tray, board,
+        &rules, &rules.dictionary, placements, usize::MAX, DEFAULT_PLACEMENTS_PER_TASK,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn evaluate_placements<'a>(
+    arenas_str: &'a Arenas<u8>,
+    arenas_str_list: &'a Arenas<StrList<'a>>,
+    arenas_mov: &'a Arenas<(usize, LetterTile)>,
+    tray: &TrayRemaining, board: &Board,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    dictionary: &Set<impl AsRef<[u8]> + Sync>,
+    placements: Vec<(Placement, impl RestrictedLine + Send, usize)>,
+    max_moves: usize,
+    placements_per_task: usize,
+) -> EvaluationResult<'a> {
+    use rayon::prelude::*;
+    
+    let mut must_play = [0; 256];
+    rules.must_play.iter().for_each(|l| must_play[l.0 as usize] += 1);
     
     let found_moves: DashMap<Move, &StrList> = DashMap::new();
+    let counters = profile::Counters::default();
     
-    prepared_v.explore().chain(prepared_h.explore())
-    .collect::<Vec<_>>()
-    .into_par_iter()
-    .for_each_init(
-        || (arenas_str.new_arena(), arenas_mov.new_arena(), arenas_str_list.new_arena()),
-        |(arena_str, arena_mov, arena_str_list), (
-            placement,
-            restr_slice,
-            min_len,
-        )| {
-            let automaton = ScrabbleAutomata {
-                line: restr_slice,
-                tray: tray.clone(),
+    let truncated = if max_moves == usize::MAX {
+        placements
+        .into_par_iter()
+        .with_min_len(placements_per_task.max(1))
+        .for_each_init(
+            || (arenas_str.new_arena(), arenas_mov.new_arena(), arenas_str_list.new_arena()),
+            |(arena_str, arena_mov, arena_str_list), (
+                placement,
+                restr_slice,
                 min_len,
-                wildcards_have_multi_meaning: rules.wildcards_have_multi_meaning,
-            };
-            
-            let mut wildcards_intersection = vec![];
-            let mut moves = Vec::new();
-            let mut others = Vec::new();
-            
-            let mut matches = dictionary.search_with_state(automaton).into_stream();
-            while let Some((word, state)) = matches.next() {
-                use word_finder::{WildcardAssignment, WildcardAssignmentList};
-                
-                wildcards_intersection.clear();
-                wildcards_intersection.extend(std::iter::repeat(false).take(word.len()));
-                let mut wildcards_missing = [0; 256];
-                
-                let mut wild_list = state.unwrap().wildcards;
-                while let WildcardAssignmentList::Elem(wild_assignment, rem) = wild_list {
-                    wild_list = (*rem).clone();
-                    match wild_assignment {
-                        WildcardAssignment::Intersection(i) => wildcards_intersection[i] = true,
-                        WildcardAssignment::MissingLetter(l) => wildcards_missing[l as usize] += 1,
-                    }
-                }
-                
-                others.clear();
-                
-                generate_moves_for_word(
-                    /*current_place*/ placement,
-                    /*first*/ None,
-                    /*others*/ &mut others,
-                    /*sub_slice*/ restr_slice, word,
-                    &wildcards_intersection[..], &wildcards_missing,
-                    &mut moves, arena_mov
+            )| {
+                moves_at_placement(
+                    placement, restr_slice, min_len,
+                    tray, rules, dictionary, must_play,
+                    arena_mov, &counters,
+                    |a_move, word| {
+                        let mut entry = found_moves.entry(a_move).or_insert(&StrList::EMPTY_LIST);
+                        
+                        let str_on_arena = arena_str.alloc_str(std::str::from_utf8(word).unwrap());
+                        
+                        let list = arena_str_list.alloc(StrList::Elem(str_on_arena, entry.value()));
+                        
+                        *entry.value_mut() = list;
+                    },
                 );
-                
-                for a_move in moves.drain(..) {
-                    let str_on_arena = arena_str.alloc_str(std::str::from_utf8(word).unwrap());
-                    
-                    let mut entry = found_moves.entry(a_move).or_insert(&StrList::EMPTY_LIST); //.push(str_on_arena)
-                    
-                    let list = arena_str_list.alloc(StrList::Elem(str_on_arena, entry.value()));
-                    
-                    *entry.value_mut() = list;
-                }
             }
+        );
+        false
+    } else {
+        // each placement keeps its best moves, which are then merged two by two
+        let best = placements
+        .into_par_iter()
+        .with_min_len(placements_per_task.max(1))
+        .map_init(
+            || (arenas_str.new_arena(), arenas_mov.new_arena()),
+            |(arena_str, arena_mov), (
+                placement,
+                restr_slice,
+                min_len,
+            )| {
+                let mut best = BestMoves::new(max_moves);
+                moves_at_placement(
+                    placement, restr_slice, min_len,
+                    tray, rules, dictionary, must_play,
+                    arena_mov, &counters,
+                    |a_move, word| {
+                        let word = arena_str.alloc_str(std::str::from_utf8(word).unwrap());
+                        let score = score::score_with_words(board, &a_move, &[word], &rules.score_rules);
+                        best.insert(a_move, score, vec![word]);
+                    },
+                );
+                best
+            }
+        )
+        .reduce(|| BestMoves::new(max_moves), BestMoves::merge);
+        
+        let arena_str_list = arenas_str_list.new_arena();
+        for (a_move, (_, words)) in best.moves {
+            let list = words.into_iter().fold(&StrList::EMPTY_LIST, |list, word| arena_str_list.alloc(StrList::Elem(word, list)));
+            found_moves.insert(a_move, list);
         }
-    );
+        best.truncated
+    };
     
     let mut score_per_move = vec![];
     
@@ -407,24 +1072,2061 @@ pub fn evaluate<'a>(
     .collect::<Vec<_>>()
     .into_par_iter()
     .map(|a_move| {
-        let mut score = score::naive_score(
-            &board.value_table,
-            &a_move,
-            &rules.score_rules,
-        );
-        // extra bonus of 50 points if we used 7 letters
-        if let Move::MultiLetters(_, _, others) = a_move {
-            if 1 + others.len() == 7 {
-                score += 50
-            }
-        }
+        // the words are only needed when the wildcards score as their letter
+        let words = match found_moves.get(a_move) {
+            Some(list) if rules.score_rules.blank_scores_as_letter => list.to_vec(),
+            _ => vec![],
+        };
+        // the extra bonus for using 7 letters is already part of the score
+        let score = score::score_with_words(board, a_move, &words, &rules.score_rules);
         (a_move.clone(), score)
     }).collect_into_vec(&mut score_per_move);
     
+    score_per_move.retain(|(_, s)| *s >= rules.min_score);
     score_per_move.par_sort_unstable_by_key(|(_, s)| *s);
     
     EvaluationResult {
         words: found_moves,
         score: score_per_move,
+        truncated,
+        #[cfg(feature = "profile")]
+        stats: counters.stats(),
+    }
+}
+
+/// The moves with the highest scores among the ones inserted, at most `max_moves` of them
+///
+/// The moves are ordered by score, then by move, so that the same moves are kept
+/// whatever the order of insertion
+struct BestMoves<'a> {
+    max_moves: usize,
+    moves: HashMap<Move<'a>, (u32, Vec<&'a str>)>,
+    /// The lowest kept move is on top, the entries of the moves that were evicted
+    /// or got a higher score since are outdated and skipped
+    heap: BinaryHeap<Reverse<(u32, Move<'a>)>>,
+    /// Whether a move was dropped
+    truncated: bool,
+}
+
+impl<'a> BestMoves<'a> {
+    fn new(max_moves: usize) -> Self {
+        BestMoves {
+            max_moves,
+            moves: HashMap::new(),
+            heap: BinaryHeap::new(),
+            truncated: false,
+        }
+    }
+    
+    /// Adds the words to the move, which keeps the highest of its scores, and evicts
+    /// the lowest move when there are too many
+    fn insert(&mut self, a_move: Move<'a>, score: u32, words: Vec<&'a str>) {
+        if let Some((best_score, known)) = self.moves.get_mut(&a_move) {
+            known.extend(words);
+            if score > *best_score {
+                *best_score = score;
+                self.heap.push(Reverse((score, a_move)));
+            }
+            return
+        }
+        
+        if self.moves.len() >= self.max_moves {
+            self.truncated = true;
+            self.drop_outdated();
+            match self.heap.peek() {
+                Some(Reverse(lowest)) if (score, &a_move) > (lowest.0, &lowest.1) => {
+                    let Reverse((_, lowest)) = self.heap.pop().unwrap();
+                    self.moves.remove(&lowest);
+                },
+                _ => return,
+            }
+        }
+        self.moves.insert(a_move.clone(), (score, words));
+        self.heap.push(Reverse((score, a_move)));
+    }
+    
+    fn drop_outdated(&mut self) {
+        while let Some(Reverse((score, a_move))) = self.heap.peek() {
+            if self.moves.get(a_move).map(|(best_score, _)| best_score) == Some(score) {
+                break
+            }
+            self.heap.pop();
+        }
+    }
+    
+    fn merge(mut self, other: Self) -> Self {
+        self.truncated |= other.truncated;
+        for (a_move, (score, words)) in other.moves {
+            self.insert(a_move, score, words);
+        }
+        self
+    }
+}
+
+/// Same as `evaluate`, but `visit` is called with each move and its score as soon
+/// as it is found, instead of keeping all the moves
+///
+/// The moves are not deduplicated: a move that plays several words (with wildcards
+/// on the board) is visited once for each of them. `visit` is called from several
+/// threads, in no particular order
+pub fn evaluate_visit(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    visit: impl Fn(&Move, u32) + Sync,
+) {
+    use rayon::prelude::*;
+    
+    let dictionary = rules.cross_dictionary();
+    
+    let prepared_h = ConstrainedBoard::build(&board.letter_table, Direction::Vertical, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    let prepared_v = ConstrainedBoard::build(&board.letter_table, Direction::Horizontal, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    
+    let placements = prepared_v.explore(rules.require_attachment).chain(prepared_h.explore(rules.require_attachment)).collect::<Vec<_>>();
+    
+    let mut must_play = [0; 256];
+    rules.must_play.iter().for_each(|l| must_play[l.0 as usize] += 1);
+    
+    let counters = profile::Counters::default();
+    
+    placements
+    .into_par_iter()
+    .for_each(|(placement, restr_slice, min_len)| {
+        let arena_mov = Arena::new();
+        moves_at_placement(
+            placement, restr_slice, min_len,
+            tray, &rules, &rules.dictionary, must_play,
+            &arena_mov, &counters,
+            |a_move, word| {
+                let word = std::str::from_utf8(word).unwrap();
+                let score = score::score_with_words(board, &a_move, &[word], &rules.score_rules);
+                if score >= rules.min_score {
+                    visit(&a_move, score);
+                }
+            },
+        );
+    });
+}
+
+/// Calls `on_move` with the moves playing a word of the `dictionary` from the `placement`,
+/// and the word they play
+#[allow(clippy::too_many_arguments)]
+fn moves_at_placement<'a>(
+    placement: Placement, restr_slice: impl RestrictedLine, min_len: usize,
+    tray: &TrayRemaining,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+    dictionary: &Set<impl AsRef<[u8]>>,
+    must_play: [u8; 256],
+    arena_mov: &'a Arena<(usize, LetterTile)>,
+    #[cfg_attr(not(feature = "profile"), allow(unused_variables))]
+    counters: &profile::Counters,
+    mut on_move: impl FnMut(Move<'a>, &[u8]),
+) {
+    use fst::{IntoStreamer, Streamer};
+    use word_finder::ScrabbleAutomata;
+    
+    let max_len = match region_max_len(rules.region, placement) {
+        Some(max_len) => max_len,
+        None => return,
+    };
+    
+    let automaton = ScrabbleAutomata {
+        line: restr_slice,
+        tray: *tray,
+        min_len,
+        wildcards_have_multi_meaning: rules.wildcards_have_multi_meaning,
+        min_word_len: rules.min_main_word_len,
+        must_play,
+        bingo_tiles: rules.bingos_only.then_some(rules.score_rules.rack_size),
+        max_tiles_placed: rules.max_tiles_placed,
+        missing_budget: 0,
+    };
+    #[cfg(feature = "profile")]
+    let automaton = profile::Counted { automaton, counters };
+    
+    let mut wildcards_intersection = vec![];
+    let mut moves = Vec::new();
+    let mut others = Vec::new();
+    
+    let mut matches = dictionary.search_with_state(automaton).into_stream();
+    while let Some((word, state)) = matches.next() {
+        use word_finder::{WildcardAssignment, WildcardAssignmentList};
+        
+        if word.len() > max_len || std::str::from_utf8(word).is_ok_and(|word| rules.exclude.contains(word)) {
+            continue
+        }
+        
+        wildcards_intersection.clear();
+        wildcards_intersection.extend(std::iter::repeat_n(false, word.len()));
+        let mut wildcards_missing = [0; 256];
+        
+        let mut wild_list = state.unwrap().wildcards;
+        while let WildcardAssignmentList::Elem(wild_assignment, rem) = wild_list {
+            wild_list = (*rem).clone();
+            match wild_assignment {
+                WildcardAssignment::Intersection(i) => wildcards_intersection[i] = true,
+                WildcardAssignment::MissingLetter(l) => wildcards_missing[l as usize] += 1,
+                // there is no budget for them
+                WildcardAssignment::NotInTray(_) => unreachable!(),
+            }
+        }
+        
+        others.clear();
+        
+        generate_moves_for_word(
+            /*current_place*/ placement,
+            /*first*/ None,
+            /*others*/ &mut others,
+            /*sub_slice*/ restr_slice, word,
+            &wildcards_intersection[..], &wildcards_missing,
+            &mut moves, arena_mov
+        );
+        
+        for a_move in moves.drain(..) {
+            on_move(a_move, word);
+        }
+    }
+}
+
+/// The length of the longest word that can be played from the `placement` in the
+/// `region` (see `Rules::region`), None if the placement is out of it
+fn region_max_len(region: Option<(Position, Position)>, placement: Placement) -> Option<usize> {
+    let Placement(start, dir) = placement;
+    match region {
+        Some((top_left, bottom_right)) => {
+            let inside = [Direction::Horizontal, Direction::Vertical].iter()
+                .all(|&d| top_left[d] <= start[d] && start[d] <= bottom_right[d]);
+            if inside { Some(bottom_right[dir] - start[dir] + 1) } else { None }
+        },
+        None => Some(usize::MAX),
+    }
+}
+
+/// The best score of the moves starting at each square of the board
+///
+/// A move is counted on the square of its first tile (see `Move::first_position`),
+/// squares where no move starts have a score of 0
+pub fn score_heatmap(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> Table<u32> {
+    let arenas_str: Arenas<u8> = Arenas::new();
+    let arenas_str_list: Arenas<StrList> = Arenas::new();
+    let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+    
+    let result = evaluate(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        tray, board,
+        rules,
+    );
+    
+    let mut heatmap = Table::with_size(board.letter_table.len_in(Direction::Vertical), board.letter_table.len_in(Direction::Horizontal), 0);
+    for (a_move, score) in result.score {
+        if let Some(best) = heatmap.get_mut(a_move.first_position()) {
+            *best = score.max(*best);
+        }
+    }
+    heatmap
+}
+
+/// The scores of the moves of the `result` added up on each square they go through
+///
+/// Unlike `score_heatmap`, every move counts, on all the squares of the word it
+/// plays in its direction (see `Move::footprint`), including the tiles of the
+/// `board` it was found for
+pub fn square_value_map(result: &EvaluationResult, board: &Board) -> Table<f64> {
+    let mut values = Table::with_size(board.letter_table.len_in(Direction::Vertical), board.letter_table.len_in(Direction::Horizontal), 0.);
+    for (a_move, score) in &result.score {
+        for pos in a_move.footprint(board, false) {
+            if let Some(value) = values.get_mut(pos) {
+                *value += *score as f64;
+            }
+        }
+    }
+    values
+}
+
+/// How many points the best move loses when the square at `position` has no
+/// bonus anymore (see `score_rules::MaskedBonus`)
+///
+/// 0 if no move can be played, or if the best move does not need the bonus
+pub fn premium_square_value<Scoring: LetterScoring, Bonuses: BoardBonus, DictionaryStorage: AsRef<[u8]> + Sync>(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<Scoring, Bonuses, DictionaryStorage>,
+    position: Position,
+) -> u32 {
+    use crate::score_rules::{Bonus, MaskedBonus};
+    
+    fn best_score(
+        tray: &TrayRemaining, board: &Board,
+        rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    ) -> u32 {
+        let arenas_str: Arenas<u8> = Arenas::new();
+        let arenas_str_list: Arenas<StrList> = Arenas::new();
+        let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+        
+        let result = evaluate_with_rules(
+            &arenas_str, &arenas_str_list, &arenas_mov,
+            tray, board,
+            rules, usize::MAX, DEFAULT_PLACEMENTS_PER_TASK,
+        );
+        result.score.last().map_or(0, |(_, score)| *score)
+    }
+    
+    let with_bonus = best_score(tray, board, &rules);
+    
+    let masked_rules = rules.map_bonuses(|inner| MaskedBonus {
+        inner,
+        overrides: std::iter::once((position, Bonus { letter: 1, word: 1 })).collect(),
+    });
+    
+    with_bonus.saturating_sub(best_score(tray, board, &masked_rules))
+}
+
+/// The move with the highest score, None if no move can be played
+pub fn best_move(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> Option<(OwnedMove, u32)> {
+    best_word_move(tray, board, rules).map(|(a_move, _, score)| (a_move, score))
+}
+
+/// The horizontal and the vertical moves with the highest score, None if no move can be played in the direction
+///
+/// A single tile has no direction, so it can be the best move of both
+#[allow(clippy::type_complexity)]
+pub fn best_per_direction(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> (Option<(OwnedMove, u32)>, Option<(OwnedMove, u32)>) {
+    let arenas_str: Arenas<u8> = Arenas::new();
+    let arenas_str_list: Arenas<StrList> = Arenas::new();
+    let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+    
+    let result = evaluate(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        tray, board,
+        rules,
+    );
+    
+    let best_in = |dir| result.score.iter().rev()
+        .find(|(a_move, _)| match a_move {
+            Move::SingleLetter(_, _) => true,
+            Move::MultiLetters(place, _, _) => place.1 == dir,
+        })
+        .map(|(a_move, score)| (OwnedMove::from(a_move), *score));
+    (best_in(Direction::Horizontal), best_in(Direction::Vertical))
+}
+
+/// The tiles of the tray that extend the word at `word_placement` by one square at
+/// either end, with the letter they are played as and the score of the move
+///
+/// `word_placement` is any square of the word, in the direction of the word. The
+/// extended word and the perpendicular word formed by the tile must be allowed by
+/// the rules. A wildcard is only played as the letters that are not in the tray
+pub fn extension_scores(
+    board: &Board, word_placement: Placement, tray: &TrayRemaining,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> Vec<(Position, Letter, u32)> {
+    let dir = word_placement.1;
+    let size = board.letter_table.len_in(dir);
+    let filled = |place: &Placement| board.letter_table.get(place.0).and_then(Square::tile).is_some();
+    
+    let mut start = word_placement;
+    while let Some(back) = start.try_back(size).filter(filled) {
+        start = back;
+    }
+    let mut end = word_placement;
+    while let Some(next) = end.try_next(size).filter(filled) {
+        end = next;
+    }
+    
+    let allowed = |word: &[LetterTile], dictionary| word.len() < 2 || (
+        crate::matches_a_word(word, dictionary)
+        && !rules.exclude.contains(&word.iter().map(LetterTile::to_string).collect::<String>())
+    );
+    
+    let mut extensions = vec![];
+    for place in start.try_back(size).into_iter().chain(end.try_next(size)).filter(|place| !filled(place)) {
+        for byte in b'a'..=b'z' {
+            let tile = if tray.remove(byte).is_some() {
+                LetterTile::Letter(Letter(byte))
+            } else if tray.remove_wildcard().is_some() {
+                LetterTile::Wildcard
+            } else {
+                continue
+            };
+            let placed = [(place.0, LetterTile::Letter(Letter(byte)))];
+            let main_word = crate::word_through(board, &placed, place.0, dir);
+            let perp_word = crate::word_through(board, &placed, place.0, dir.perp());
+            if allowed(&main_word, &rules.dictionary) && allowed(&perp_word, rules.cross_dictionary()) {
+                let a_move = Move::SingleLetter(place.0, tile);
+                let word = main_word.iter().map(LetterTile::to_string).collect::<String>();
+                extensions.push((place.0, Letter(byte), score::score_with_words(board, &a_move, &[&word], &rules.score_rules)));
+            }
+        }
+    }
+    extensions
+}
+
+/// Same as `best_move`, with the word played by the move
+///
+/// When the move can be read as several words (because of wildcards on the board),
+/// the first one in alphabetical order is given
+pub fn best_word_move(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> Option<(OwnedMove, String, u32)> {
+    let arenas_str: Arenas<u8> = Arenas::new();
+    let arenas_str_list: Arenas<StrList> = Arenas::new();
+    let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+    
+    let result = evaluate(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        tray, board,
+        rules,
+    );
+    
+    let (a_move, score) = result.score.last()?;
+    let word = result.words.get(a_move)?.to_vec().into_iter().min()?;
+    Some((OwnedMove::from(a_move), word.to_owned(), *score))
+}
+
+/// Whether the move is a vertical one on an empty board
+///
+/// With bonuses that are symmetric along the diagonal (like `ScrabbleBonus`), it has
+/// the same score as the horizontal move with the same tiles, so it can be hidden
+pub fn is_transposed_first_move(board: &Board, a_move: &Move) -> bool {
+    matches!(a_move, Move::MultiLetters(Placement(_, Direction::Vertical), _, _))
+    && board.is_empty()
+}
+
+/// The moves whose word in their direction has two letters, with their score and the
+/// words they form (the returned vec is sorted by score)
+///
+/// The words are the two-letter one, then the ones formed in the perpendicular direction
+/// (see `crate::cross_words`), which can be longer. Only the two-letter words of the
+/// dictionary are searched, instead of stopping the search of longer words
+pub fn two_letter_plays(
+    board: &Board, tray: &TrayRemaining,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> Vec<(OwnedMove, u32, Vec<String>)> {
+    use fst::Automaton;
+    
+    struct TwoLetters;
+    
+    impl Automaton for TwoLetters {
+        type State = usize;
+        fn start(&self) -> Self::State {
+            0
+        }
+        fn is_match(&self, state: &Self::State) -> bool {
+            *state == 2
+        }
+        fn accept(&self, state: &Self::State, _byte: u8) -> Self::State {
+            state + 1
+        }
+        fn can_match(&self, state: &Self::State) -> bool {
+            *state <= 2
+        }
+    }
+    
+    // the words of the search are sorted and written in memory, so it cannot fail
+    let mut twos = fst::SetBuilder::memory();
+    if twos.extend_stream(rules.dictionary.search(TwoLetters)).is_err() {
+        return vec![]
+    }
+    let twos = twos.into_set();
+    
+    let arenas_str: Arenas<u8> = Arenas::new();
+    let arenas_str_list: Arenas<StrList> = Arenas::new();
+    let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+    
+    let dictionary = rules.cross_dictionary();
+    
+    let prepared_h = ConstrainedBoard::build(&board.letter_table, Direction::Vertical, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    let prepared_v = ConstrainedBoard::build(&board.letter_table, Direction::Horizontal, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    
+    let placements = prepared_v.explore(rules.require_attachment).chain(prepared_h.explore(rules.require_attachment)).collect::<Vec<_>>();
+    
+    let result = evaluate_placements(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        tray, board,
+        rules, &twos, placements, usize::MAX, DEFAULT_PLACEMENTS_PER_TASK,
+    );
+    
+    result.score.iter().map(|(a_move, score)| {
+        let mut words = result.words.get(a_move).map_or(vec![], |list| list.to_vec())
+            .into_iter().map(str::to_owned).collect::<Vec<_>>();
+        let mut cross_words = crate::cross_words(board, a_move);
+        if let Move::SingleLetter(_, _) = a_move {
+            // the words of a single tile in both directions include its two-letter word
+            if let Some(i) = cross_words.iter().position(|word| words.contains(word)) {
+                cross_words.remove(i);
+            }
+        }
+        words.extend(cross_words);
+        (OwnedMove::from(a_move), *score, words)
+    }).collect()
+}
+
+/// The moves of the first turn, covering the center of an empty board, with their score
+/// (the returned vec is sorted)
+///
+/// These are the moves of `evaluate` on an empty board, found without building the
+/// restrictions of the board since there is no perpendicular word. The moves away from
+/// the center allowed without `Rules::require_attachment` are not included
+pub fn opening_moves(
+    tray: &TrayRemaining,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+) -> Vec<(OwnedMove, u32)> {
+    use rayon::prelude::*;
+    
+    let board = Board::empty();
+    let center = board.letter_table.center();
+    let line = [RestrictedSquare::Empty(LetterSet::any()); BOARD_SIZE];
+    
+    let placements = [Direction::Horizontal, Direction::Vertical].iter().flat_map(|&dir| {
+        let mut start = Placement(Position { row: 0, col: 0 }, dir);
+        start.0[dir.perp()] = center[dir.perp()];
+        explore_line_with(start, |_| false, |_| true, BOARD_SIZE, Some(center))
+    }).collect::<Vec<_>>();
+    
+    let mut must_play = [0; 256];
+    rules.must_play.iter().for_each(|l| must_play[l.0 as usize] += 1);
+    
+    let counters = profile::Counters::default();
+    
+    let mut moves = placements
+    .into_par_iter()
+    .flat_map_iter(|(placement, min_len)| {
+        let arena_mov = Arena::new();
+        // the same move can play several words with wildcards, the best score is kept
+        let mut scores = HashMap::new();
+        moves_at_placement(
+            placement, &line[placement.0[placement.1]..], min_len,
+            tray, &rules, &rules.dictionary, must_play,
+            &arena_mov, &counters,
+            |a_move, word| {
+                let word = std::str::from_utf8(word).unwrap();
+                let score = score::score_with_words(&board, &a_move, &[word], &rules.score_rules);
+                let best = scores.entry(OwnedMove::from(&a_move)).or_insert(score);
+                *best = score.max(*best);
+            },
+        );
+        scores.into_iter()
+    })
+    .filter(|(_, score)| *score >= rules.min_score)
+    .collect::<Vec<_>>();
+    
+    moves.sort_unstable_by(|(m1, s1), (m2, s2)| s1.cmp(s2).then_with(|| m1.cmp(m2)));
+    moves
+}
+
+/// The words of the `dictionary` that can be made with the tiles of the board and
+/// of the tray together, wherever they are, the longest ones first
+///
+/// The tiles of the board are the letters they are used as (in uppercase for
+/// wildcards), and the wildcards of any meaning (`*`) can be used as any letter,
+/// like the ones of the tray. Words of the same length are in alphabetical order
+pub fn words_from_all_tiles(board: &Board, tray: &TrayRemaining, dictionary: &Set<impl AsRef<[u8]>>) -> Vec<String> {
+    use fst::{IntoStreamer, Streamer};
+    
+    let mut letters = [0u8; 256];
+    let mut n_wildcards = 0;
+    let board_tiles = board.letter_table.iter_positions().filter_map(|(_, square)| square.tile().copied());
+    for tile in board_tiles.chain(tray.tiles()) {
+        match tile {
+            // no word is made with the letters a tray cannot hold
+            LetterTile::Letter(l) if !Alphabet::english().contains(l) => {},
+            LetterTile::Letter(l) => letters[l.0 as usize] += 1,
+            LetterTile::Wildcard => n_wildcards += 1,
+        }
+    }
+    let tray = TrayRemaining::new(letters, n_wildcards).expect("only the letters of the alphabet are counted");
+    let automaton = word_finder::TilesAutomaton { tray };
+    
+    let mut words = vec![];
+    let mut matches = dictionary.search(automaton).into_stream();
+    while let Some(word) = matches.next() {
+        words.push(String::from_utf8_lossy(word).into_owned());
+    }
+    // the stream is in alphabetical order
+    words.sort_by_key(|word| std::cmp::Reverse(word.len()));
+    words
+}
+
+/// The words that could be played with at most `missing_budget` more letters
+/// in the tray, with the letters missing (sorted), for example to learn the words
+/// that were a single tile away
+///
+/// The words that can already be played at the same place are not listed, the
+/// returned vec is sorted and has no duplicates
+pub fn near_miss_words(
+    board: &Board, tray: &TrayRemaining,
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+    missing_budget: u8,
+) -> Vec<(String, Vec<Letter>)> {
+    use fst::{IntoStreamer, Streamer};
+    use word_finder::{ScrabbleAutomata, WildcardAssignment, WildcardAssignmentList};
+    
+    let dictionary = rules.cross_dictionary();
+    
+    let prepared_h = ConstrainedBoard::build(&board.letter_table, Direction::Vertical, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    let prepared_v = ConstrainedBoard::build(&board.letter_table, Direction::Horizontal, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    
+    let mut must_play = [0; 256];
+    rules.must_play.iter().for_each(|l| must_play[l.0 as usize] += 1);
+    
+    let mut near_misses = std::collections::BTreeSet::new();
+    for (placement, restr_slice, min_len) in prepared_v.explore(rules.require_attachment).chain(prepared_h.explore(rules.require_attachment)) {
+        let max_len = match region_max_len(rules.region, placement) {
+            Some(max_len) => max_len,
+            None => continue,
+        };
+        let automaton = ScrabbleAutomata {
+            line: restr_slice,
+            tray: *tray,
+            min_len,
+            wildcards_have_multi_meaning: rules.wildcards_have_multi_meaning,
+            min_word_len: rules.min_main_word_len,
+            must_play,
+            bingo_tiles: rules.bingos_only.then_some(rules.score_rules.rack_size),
+            max_tiles_placed: rules.max_tiles_placed,
+            missing_budget,
+        };
+        let mut matches = rules.dictionary.search_with_state(automaton).into_stream();
+        while let Some((word, state)) = matches.next() {
+            let word = String::from_utf8_lossy(word);
+            if word.len() > max_len || rules.exclude.contains(&*word) {
+                continue
+            }
+            let mut missing = vec![];
+            let mut wild_list = state.unwrap().wildcards;
+            while let WildcardAssignmentList::Elem(wild_assignment, rem) = wild_list {
+                wild_list = (*rem).clone();
+                if let WildcardAssignment::NotInTray(l) = wild_assignment {
+                    missing.push(Letter(l));
+                }
+            }
+            if !missing.is_empty() {
+                missing.sort();
+                near_misses.insert((word.into_owned(), missing));
+            }
+        }
+    }
+    near_misses.into_iter().collect()
+}
+
+/// The moves that play the given `word`, with their score (the returned vec is sorted)
+///
+/// The moves can use the tiles of the board, and the words formed in the
+/// perpendicular direction are still checked. There is no move if the word
+/// contains something else than ascii letters
+pub fn placements_for_word(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    word: &str,
+) -> Vec<(OwnedMove, u32)> {
+    let word_dictionary = match crate::build_dictionary(std::iter::once(word)) {
+        Ok(dictionary) => dictionary,
+        Err(_) => return vec![],
+    };
+    
+    let arenas_str: Arenas<u8> = Arenas::new();
+    let arenas_str_list: Arenas<StrList> = Arenas::new();
+    let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+    
+    let dictionary = rules.cross_dictionary();
+    
+    let prepared_h = LazyConstrainedBoard::new(&board.letter_table, Direction::Vertical, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    let prepared_v = LazyConstrainedBoard::new(&board.letter_table, Direction::Horizontal, dictionary, &rules.exclude).with_first_move(rules.first_move);
+    
+    let placements = prepared_v.explore(rules.require_attachment).chain(prepared_h.explore(rules.require_attachment)).collect::<Vec<_>>();
+    
+    let result = evaluate_placements(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        tray, board,
+        &rules, &word_dictionary, placements, usize::MAX, DEFAULT_PLACEMENTS_PER_TASK,
+    );
+    
+    result.score.iter()
+    .map(|(a_move, score)| (OwnedMove::from(a_move), *score))
+    .collect()
+}
+
+/// The move playing the given `word` with the highest score, None if it cannot be played
+///
+/// As for `placements_for_word`, the move can use wildcards and the tiles of the board.
+/// Among moves with the same score, the first one in the order of the moves is given
+pub fn best_placement_for_word(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    word: &str,
+) -> Option<(OwnedMove, u32)> {
+    placements_for_word(tray, board, rules, word).into_iter()
+    .max_by(|(move_a, score_a), (move_b, score_b)| score_a.cmp(score_b).then_with(|| move_b.cmp(move_a)))
+}
+
+/// The moves placing a tile on at least one of the `block` squares, with their score
+///
+/// This is useful to block a square the opponent could use, the returned vec is sorted by score
+pub fn blocking_moves(
+    tray: &TrayRemaining, board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    block: &[Position],
+) -> Vec<(OwnedMove, u32)> {
+    let arenas_str: Arenas<u8> = Arenas::new();
+    let arenas_str_list: Arenas<StrList> = Arenas::new();
+    let arenas_mov: Arenas<(usize, LetterTile)> = Arenas::new();
+    
+    let result = evaluate(
+        &arenas_str, &arenas_str_list, &arenas_mov,
+        tray, board,
+        rules,
+    );
+    
+    result.score.iter()
+    .filter(|(a_move, _)| a_move.positions().any(|pos| block.contains(&pos)))
+    .map(|(a_move, score)| (OwnedMove::from(a_move), *score))
+    .collect()
+}
+
+/// How far from a placed tile the squares opened by a move are counted in `exposure_penalty`
+pub const EXPOSURE_REACH: usize = 7;
+
+/// How much the move opens the word bonuses of the board to the opponent
+///
+/// `board_after` is the board once the move is played. Each empty square with a
+/// word bonus that is at most `EXPOSURE_REACH` squares away from a tile placed by
+/// the move, in its row or column and with only empty squares between them, counts
+/// for its word multiplier minus one (so a triple word square counts for 2)
+pub fn exposure_penalty(board_after: &Board, a_move: &Move, bonuses: &impl BoardBonus) -> f64 {
+    let steps: [fn(Placement, usize) -> Option<Placement>; 2] = [Placement::try_next, Placement::try_back];
+    let mut exposed = HashSet::new();
+    for pos in a_move.positions() {
+        for &dir in &[Direction::Horizontal, Direction::Vertical] {
+            let size = board_after.letter_table.len_in(dir);
+            for step in &steps {
+                let mut place = Placement(pos, dir);
+                for _ in 0..EXPOSURE_REACH {
+                    match step(place, size) {
+                        Some(next) if board_after.letter_table.get(next.0) == Some(&Square::Empty) => place = next,
+                        _ => break,
+                    }
+                    if bonuses.bonus_at(place.0).word > 1 {
+                        exposed.insert(place.0);
+                    }
+                }
+            }
+        }
     }
+    exposed.into_iter().map(|pos| (bonuses.bonus_at(pos).word - 1) as f64).sum()
+}
+
+/// The moves of the result ranked by their score minus `weight` times their `exposure_penalty` (the returned vec is sorted)
+pub fn rank_by_exposure<'a>(
+    result: &EvaluationResult<'a>, board: &Board,
+    bonuses: &impl BoardBonus, weight: f64,
+) -> Vec<(Move<'a>, f64)> {
+    let mut ranked = result.score.iter().map(|(a_move, score)| {
+        (a_move.clone(), *score as f64 - weight * exposure_penalty(&board_after(board, a_move), a_move, bonuses))
+    }).collect::<Vec<_>>();
+    ranked.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    ranked
+}
+
+/// The board once the tiles of the move are placed on it
+fn board_after(board: &Board, a_move: &Move) -> Board {
+    let mut board_after = board.clone();
+    for (pos, tile) in a_move.positions().zip(a_move.placed_tiles()) {
+        board_after.letter_table.set(pos, Square::Filled(tile));
+    }
+    board_after
+}
+
+/// The points a rough heuristic gives to the tiles left in the tray after a move, for english
+///
+/// Each tile has a value (wildcards and s are kept, q and v are to be played), each
+/// copy of a letter after the first one costs 3 points, and each vowel or consonant
+/// beyond one more than the others costs 2 points. Wildcards count as neither
+pub fn leave_value(leave: &TrayRemaining) -> f64 {
+    const TILE_VALUES: [f64; 26] = [
+        // a    b     c    d    e    f     g     h    i     j     k     l     m
+        1.0, -2.0, 0.5, 0.0, 1.5, -2.0, -2.0, 1.0, -0.5, -1.5, -0.5, -0.5, 0.5,
+        // n   o     p     q     r    s    t    u     v     w     x    y     z
+        0.5, -1.0, -0.5, -7.0, 1.0, 8.0, 0.5, -3.5, -5.5, -4.0, 3.5, -0.5, 2.0,
+    ];
+    
+    let mut value = 0.;
+    for (i, &tile_value) in TILE_VALUES.iter().enumerate() {
+        let n = leave.count(b'a' + i as u8) as f64;
+        if n > 0. {
+            value += n * tile_value - 3. * (n - 1.);
+        }
+    }
+    value += 25. * leave.tiles().filter(|&tile| tile == LetterTile::Wildcard).count() as f64;
+    
+    let (n_vowels, n_consonants) = leave.vowel_consonant_counts();
+    value - 2. * (n_vowels.abs_diff(n_consonants).saturating_sub(1)) as f64
+}
+
+/// The moves of the result with their score and their equity, the score plus the
+/// `leave_eval` of what the move leaves in the `tray` (the returned vec is sorted by equity)
+pub fn rank_by_equity<'a>(
+    result: &EvaluationResult<'a>, tray: &TrayRemaining,
+    leave_eval: impl Fn(&TrayRemaining) -> f64,
+) -> Vec<(Move<'a>, u32, f64)> {
+    let mut ranked = result.score.iter().map(|(a_move, score)| {
+        let leave = tray.after_move(a_move).expect("the move plays tiles that are not in the tray");
+        (a_move.clone(), *score, *score as f64 + leave_eval(&leave))
+    }).collect::<Vec<_>>();
+    ranked.sort_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+    ranked
+}
+
+/// Same as `rank_by_equity`, where the moves of equal equity are ranked by their
+/// `exposure_penalty` on the `board` times `exposure_weight`, exposing less being better
+///
+/// The moves that are still equal are then in the order of `Move`, so that the
+/// ranking is always the same. An `exposure_weight` of 0 leaves the exposure out
+pub fn rank_by_equity_and_exposure<'a>(
+    result: &EvaluationResult<'a>, board: &Board, tray: &TrayRemaining,
+    leave_eval: impl Fn(&TrayRemaining) -> f64,
+    bonuses: &impl BoardBonus, exposure_weight: f64,
+) -> Vec<(Move<'a>, u32, f64)> {
+    let mut ranked = rank_by_equity(result, tray, leave_eval).into_iter().map(|(a_move, score, equity)| {
+        let exposure = if exposure_weight == 0. {
+            0.
+        } else {
+            exposure_weight * exposure_penalty(&board_after(board, &a_move), &a_move, bonuses)
+        };
+        (a_move, score, equity, exposure)
+    }).collect::<Vec<_>>();
+    // the best move is the last one
+    ranked.sort_by(|(move_a, _, equity_a, exposure_a), (move_b, _, equity_b, exposure_b)| {
+        equity_a.total_cmp(equity_b)
+        .then(exposure_b.total_cmp(exposure_a))
+        .then_with(|| move_a.cmp(move_b))
+    });
+    ranked.into_iter().map(|(a_move, score, equity, _)| (a_move, score, equity)).collect()
+}
+
+/// The wildcards of the move that could be put on other tiles of the move, for the same `word`
+///
+/// Each assignment is the indices (in `Move::placed_tiles`) of the tiles that are wildcards.
+/// They have the same number of wildcards for each letter as the move, which is one of them.
+/// Unlike the solver, this enumerates all the assignments instead of only keeping one of them.
+/// There is none if the move does not play the `word` in its direction
+pub fn wildcard_alternatives(
+    board: &Board,
+    rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+    a_move: &Move, word: &str,
+) -> Vec<Vec<usize>> {
+    let place = match a_move {
+        Move::SingleLetter(_, LetterTile::Wildcard) => return vec![vec![0]],
+        Move::SingleLetter(_, _) => return vec![vec![]],
+        Move::MultiLetters(place, _, _) => *place,
+    };
+    let dir = place.1;
+    
+    let mut begin_word = place;
+    while let Some(back) = begin_word.try_back(board.letter_table.len_in(dir)).filter(|back| matches!(board.letter_table.get(back.0), Some(Square::Filled(_)))) {
+        begin_word = back;
+    }
+    
+    // the letter of each placed tile, and the letters it can be for the perpendicular word
+    let mut placed = vec![];
+    for (pos, tile) in a_move.positions().zip(a_move.placed_tiles()) {
+        let letter = match word.as_bytes().get(pos[dir].wrapping_sub(begin_word.0[dir])) {
+            Some(&byte) => Letter(byte),
+            None => return vec![],
+        };
+        if tile != LetterTile::Wildcard && tile != LetterTile::Letter(letter) {
+            return vec![]
+        }
+        
+        let line = (0..board.letter_table.len_in(dir.perp())).map(|i| {
+            let mut square = pos;
+            square[dir.perp()] = i;
+            *board.letter_table.get(square).unwrap()
+        }).collect::<Vec<_>>();
+        let allowed = match restrictionner::find_restriction(&line, pos[dir.perp()], rules.cross_dictionary(), &rules.exclude) {
+            RestrictedSquare::Empty(letter_set) => letter_set.contains(letter),
+            RestrictedSquare::Filled(_) => return vec![],
+        };
+        placed.push((letter, tile == LetterTile::Wildcard, allowed));
+    }
+    
+    let mut letters = placed.iter().map(|&(letter, _, _)| letter).collect::<Vec<_>>();
+    letters.sort_unstable_by_key(|l| l.0);
+    letters.dedup();
+    
+    // wildcards where the letter is not allowed (with `wildcards_have_multi_meaning`) cannot be moved
+    let fixed = (0..placed.len()).filter(|&i| placed[i].1 && !placed[i].2).collect::<Vec<_>>();
+    
+    let mut alternatives = vec![fixed];
+    for letter in letters {
+        let free = (0..placed.len()).filter(|&i| placed[i].0 == letter && placed[i].2).collect::<Vec<_>>();
+        let n_wildcards = free.iter().filter(|&&i| placed[i].1).count();
+        let choices = combinations(&free, n_wildcards);
+        alternatives = alternatives.iter().flat_map(|alternative| choices.iter().map(move |choice| {
+            let mut alternative = alternative.clone();
+            alternative.extend(choice);
+            alternative
+        })).collect();
+    }
+    
+    alternatives.iter_mut().for_each(|alternative| alternative.sort_unstable());
+    alternatives.sort_unstable();
+    alternatives
+}
+
+/// The score of the move playing `word`, where the wildcards are on the tiles at
+/// `blank_positions` (indices in `Move::placed_tiles`) and the other tiles are letters
+///
+/// This scores one of the assignments of `wildcard_alternatives`, which can score
+/// differently when a wildcard leaves a letter bonus to a real tile. The legality of
+/// the move is not checked. There is no score if the move does not play the `word`
+pub fn score_with_blank_at(
+    board: &Board, a_move: &Move, word: &str, blank_positions: &[usize],
+    rules: &Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]>>,
+) -> Option<u32> {
+    let mut tiles = crate::resolved_tiles(board, a_move, word)?;
+    for &i in blank_positions {
+        *tiles.get_mut(i)? = LetterTile::Wildcard;
+    }
+    
+    let assigned = match a_move {
+        Move::SingleLetter(pos, _) => OwnedMove::SingleLetter(*pos, tiles[0]),
+        Move::MultiLetters(place, _, others) => OwnedMove::MultiLetters(
+            *place,
+            tiles[0],
+            others.iter().zip(&tiles[1..]).map(|(&(step, _), &tile)| (step, tile)).collect(),
+        ),
+    };
+    Some(score::score_with_words(board, &assigned.as_move(), &[word], &rules.score_rules))
+}
+
+/// All the ways to pick `k` items, in the order of `items`
+fn combinations(items: &[usize], k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        vec![vec![]]
+    } else if items.len() < k {
+        vec![]
+    } else {
+        let mut with_first = combinations(&items[1..], k - 1);
+        with_first.iter_mut().for_each(|combination| combination.insert(0, items[0]));
+        with_first.extend(combinations(&items[1..], k));
+        with_first
+    }
+}
+
+/// The expected value of the tray after exchanging the tiles of `tray` that are not in `keep`
+///
+/// The tiles drawn to fill the tray back to its size are sampled `sims` times from
+/// the `bag` (the tiles that are neither on the board nor in the tray), and the
+/// value of each resulting tray is given by `leave_eval`. The exchanged tiles are
+/// not in the bag, as they are put back after drawing. The draws are made by a
+/// generator seeded with `seed`, so the same arguments give the same value
+pub fn exchange_value(
+    tray: &TrayRemaining, bag: &TrayRemaining, keep: &TrayRemaining,
+    leave_eval: impl Fn(&TrayRemaining) -> f64, seed: u64, sims: usize,
+) -> f64 {
+    let mut bag = bag.tiles().collect::<Vec<_>>();
+    let kept = keep.tiles().collect::<Vec<_>>();
+    let n_drawn = tray.tiles().count().saturating_sub(kept.len()).min(bag.len());
+    
+    let mut state = seed;
+    let sims = sims.max(1);
+    let total = (0..sims).map(|_| {
+        // partial Fisher-Yates shuffle, the drawn tiles being the first ones of the bag
+        for i in 0..n_drawn {
+            let j = i + (split_mix(&mut state) % (bag.len() - i) as u64) as usize;
+            bag.swap(i, j);
+        }
+        
+        let mut letters = [0; 256];
+        let mut n_wildcards = 0;
+        for tile in kept.iter().chain(&bag[..n_drawn]) {
+            match tile {
+                LetterTile::Letter(l) => letters[l.0 as usize] += 1,
+                LetterTile::Wildcard => n_wildcards += 1,
+            }
+        }
+        leave_eval(&TrayRemaining::new(letters, n_wildcards).expect("the tiles come from trays"))
+    }).sum::<f64>();
+    
+    total / sims as f64
+}
+
+/// The next number of the SplitMix64 generator
+pub(crate) fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[test]
+fn bingo_bonus_counted_once() {
+    let mut letters = [0; 256];
+    b"abcdefg".iter().for_each(|&l| letters[l as usize] += 1);
+    let tray = TrayRemaining::new(letters, 0).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(
+        &tray, &board,
+        crate::test_utils::rules(&["abcdefg"]),
+    );
+    
+    assert!(!result.score.is_empty());
+    for (a_move, score) in &result.score {
+        assert_eq!(*score, score::naive_score(&board.value_table, a_move, &crate::test_utils::score_rules()));
+    }
+}
+
+#[test]
+fn lazy_same_as_eager() {
+    let rules = || crate::test_utils::rules(&["at", "cat", "cats", "act", "acts", "ta", "tas", "sat", "scat", "tact"]);
+    
+    let mut letters = [0; 256];
+    b"ast".iter().for_each(|&l| letters[l as usize] += 1);
+    let tray = TrayRemaining::new(letters, 1).unwrap();
+    
+    let mut board = Board::empty();
+    for (i, &l) in b"cat".iter().enumerate() {
+        let square = Square::Filled(LetterTile::Letter(Letter(l)));
+        board.letter_table.set(Position { row: 7, col: 6 + i }, square);
+        board.value_table.set(Position { row: 7, col: 6 + i }, square);
+    }
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let eager = arenas.evaluate(&tray, &board, rules());
+    let lazy = evaluate_lazy(&arenas.strs, &arenas.str_lists, &arenas.moves, &tray, &board, rules());
+    
+    assert!(!eager.score.is_empty());
+    // compares the debug output, as it must be deterministic
+    assert_eq!(
+        format!("{:?}", eager.into_owned()),
+        format!("{:?}", lazy.into_owned()),
+    );
+}
+
+#[test]
+fn cross_dictionary_for_perpendicular_words() {
+    let rules = |cross_words: Option<Vec<&str>>| Rules {
+        cross_dictionary: cross_words.map(|words| crate::build_dictionary(words).unwrap()),
+        ..crate::test_utils::rules(&["as"])
+    };
+    
+    let mut letters = [0; 256];
+    b"as".iter().for_each(|&l| letters[l as usize] += 1);
+    let tray = TrayRemaining::new(letters, 0).unwrap();
+    
+    let mut board = Board::empty();
+    for (i, &l) in b"cat".iter().enumerate() {
+        let square = Square::Filled(LetterTile::Letter(Letter(l)));
+        board.letter_table.set(Position { row: 7, col: 6 + i }, square);
+        board.value_table.set(Position { row: 7, col: 6 + i }, square);
+    }
+    
+    // "as" played downward, making "cats" with the last letter
+    let others = [(0, LetterTile::Letter(Letter(b's')))];
+    let mov = Move::MultiLetters(
+        Placement(Position { row: 6, col: 9 }, Direction::Vertical),
+        LetterTile::Letter(Letter(b'a')),
+        &others,
+    );
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let same = arenas.evaluate(&tray, &board, rules(None));
+    assert!(same.words.get(&mov).is_none());
+    
+    let split = arenas.evaluate(&tray, &board, rules(Some(vec!["cats"])));
+    assert!(split.words.get(&mov).is_some());
+}
+
+#[test]
+fn blocking_moves_cover_the_square() {
+    let rules = crate::test_utils::rules(&["at", "cat", "tac"]);
+    
+    let tray = TrayRemaining::parse("act", crate::Strictness::Strict).unwrap();
+    let block = [Position { row: 9, col: 7 }];
+    
+    let moves = blocking_moves(&tray, &Board::empty(), rules, &block);
+    
+    // only the 3 letter words played downward from the center reach the square
+    assert_eq!(moves.len(), 2);
+    for (a_move, _) in &moves {
+        assert_eq!(a_move.as_move().positions().last(), Some(block[0]));
+    }
+}
+
+
+#[test]
+fn first_move_in_both_directions() {
+    let rules = crate::test_utils::rules(&["a", "at"]);
+    
+    let tray = TrayRemaining::parse("at", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    
+    // a single letter on the center is not a move, as the first word needs 2 letters
+    let center = Move::SingleLetter(Position { row: 7, col: 7 }, LetterTile::Letter(Letter(b'a')));
+    assert!(result.words.get(&center).is_none());
+    
+    // "at" played through the center from both of its squares, in both directions
+    let (transposed, kept): (Vec<_>, Vec<_>) = result.score.iter()
+    .partition(|(a_move, _)| is_transposed_first_move(&board, a_move));
+    assert_eq!(transposed.len(), 2);
+    assert_eq!(kept.len(), 2);
+    for ((vertical, v_score), (horizontal, h_score)) in transposed.iter().zip(&kept) {
+        assert_eq!(v_score, h_score);
+        assert_eq!(vertical.placed_tiles().collect::<Vec<_>>(), horizontal.placed_tiles().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn openings_same_as_evaluate() {
+    let rules = || crate::test_utils::rules(&["a", "at", "cat", "act", "tact", "zax"]);
+    
+    let tray = TrayRemaining::parse("act*", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let openings = opening_moves(&tray, rules());
+    let result = arenas.evaluate(&tray, &board, rules());
+    
+    let mut expected = result.score.iter().map(|(a_move, score)| (OwnedMove::from(a_move), *score)).collect::<Vec<_>>();
+    expected.sort_unstable_by(|(m1, s1), (m2, s2)| s1.cmp(s2).then_with(|| m1.cmp(m2)));
+    assert!(!openings.is_empty());
+    assert_eq!(openings, expected);
+}
+
+#[test]
+fn must_play_letters() {
+    let rules = |must_play| Rules {
+        must_play,
+        ..crate::test_utils::rules(&["at", "act", "cat", "tact"])
+    };
+    
+    let tray = TrayRemaining::parse("act*", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let c = LetterTile::Letter(Letter(b'c'));
+    let result = arenas.evaluate(&tray, &board, rules(vec![Letter(b'c')]));
+    assert!(!result.score.is_empty());
+    for (a_move, _) in &result.score {
+        assert!(a_move.placed_tiles().any(|tile| tile == c), "{:?}", a_move);
+    }
+    
+    // there is a single c in the tray
+    let result = arenas.evaluate(&tray, &board, rules(vec![Letter(b'c'), Letter(b'c')]));
+    assert!(result.score.is_empty());
+}
+
+#[test]
+fn placements_of_a_word() {
+    let rules = || crate::test_utils::rules(&["at", "as", "cat", "cats", "tas"]);
+    
+    let tray = TrayRemaining::parse("as", crate::Strictness::Strict).unwrap();
+    
+    let mut board = Board::empty();
+    for (i, &l) in b"cat".iter().enumerate() {
+        let square = Square::Filled(LetterTile::Letter(Letter(l)));
+        board.letter_table.set(Position { row: 7, col: 6 + i }, square);
+        board.value_table.set(Position { row: 7, col: 6 + i }, square);
+    }
+    
+    // "tas" can only be played with the t of the board
+    let moves = placements_for_word(&tray, &board, rules(), "tas");
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].0, OwnedMove::MultiLetters(
+        Placement(Position { row: 8, col: 8 }, Direction::Vertical),
+        LetterTile::Letter(Letter(b'a')),
+        vec![(0, LetterTile::Letter(Letter(b's')))],
+    ));
+    
+    assert!(placements_for_word(&tray, &board, rules(), "it's").is_empty());
+    
+    // "cats" is better with the s than with the wildcard
+    let tray = TrayRemaining::parse("s*", crate::Strictness::Strict).unwrap();
+    assert_eq!(best_placement_for_word(&tray, &board, rules(), "cats"), Some((
+        OwnedMove::SingleLetter(Position { row: 7, col: 9 }, LetterTile::Letter(Letter(b's'))),
+        4 + 1 + 1 + 1,
+    )));
+    // "tas" through the a or the t of the board, both with the wildcard and the same score
+    assert_eq!(best_placement_for_word(&tray, &board, rules(), "tas"), Some((
+        OwnedMove::MultiLetters(
+            Placement(Position { row: 6, col: 7 }, Direction::Vertical),
+            LetterTile::Wildcard,
+            vec![(1, LetterTile::Letter(Letter(b's')))],
+        ),
+        // the wildcard scores nothing
+        1 + 1,
+    )));
+    assert_eq!(best_placement_for_word(&tray, &board, rules(), "quiz"), None);
+}
+
+#[test]
+fn max_moves_truncates() {
+    let rules = || crate::test_utils::rules(&["at", "act", "cat", "tact"]);
+    
+    let tray = TrayRemaining::parse("act*", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let all = arenas.evaluate(&tray, &board, rules());
+    assert!(!all.truncated);
+    assert!(all.score.len() > 3);
+    
+    let limited = evaluate_with_limit(&arenas.strs, &arenas.str_lists, &arenas.moves, &tray, &board, rules(), 3);
+    assert!(limited.truncated);
+    assert_eq!(limited.score.len(), 3);
+    assert_eq!(limited.words.len(), 3);
+    
+    // the kept moves are the best ones, with their words
+    let limited = limited.into_owned();
+    let all = all.into_owned();
+    assert_eq!(limited.score[..], all.score[all.score.len() - 3..]);
+    for (a_move, _) in &limited.score {
+        assert_eq!(limited.words[a_move], all.words[a_move]);
+    }
+}
+
+#[test]
+fn excluded_main_words() {
+    let rules = Rules {
+        exclude: vec!["cat".to_owned()].into_iter().collect(),
+        ..crate::test_utils::rules(&["at", "act", "cat"])
+    };
+    
+    let tray = TrayRemaining::parse("act", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    let mut words = result.words.values().flat_map(|list| list.to_vec()).collect::<Vec<_>>();
+    words.sort_unstable();
+    words.dedup();
+    assert_eq!(words, ["act", "at"]);
+}
+
+#[test]
+fn only_bingos() {
+    let rules = Rules {
+        bingos_only: true,
+        ..crate::test_utils::rules(&["ab", "abcdefg", "abcdefgh"])
+    };
+    
+    let tray = TrayRemaining::parse("abcdefgh", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    assert!(!result.score.is_empty());
+    for (a_move, _) in &result.score {
+        assert_eq!(a_move.tiles_placed(), 7, "{:?}", a_move);
+    }
+    
+    // with trays of eight tiles
+    let mut rules = Rules {
+        bingos_only: true,
+        ..crate::test_utils::rules(&["ab", "abcdefg", "abcdefgh"])
+    };
+    rules.score_rules.rack_size = 8;
+    let arenas = crate::test_utils::TestArenas::default();
+    let result = arenas.evaluate(&tray, &board, rules);
+    assert!(!result.score.is_empty());
+    for (a_move, _) in &result.score {
+        assert_eq!(a_move.tiles_placed(), 8, "{:?}", a_move);
+    }
+}
+
+#[test]
+fn limited_tiles_placed() {
+    let rules = Rules {
+        max_tiles_placed: Some(2),
+        ..crate::test_utils::rules(&["ab", "abc", "abcd", "bad", "cab"])
+    };
+    
+    let tray = TrayRemaining::parse("abcd", crate::Strictness::Strict).unwrap();
+    // "bad" can be played through this "d" with 2 tiles, but "abcd" needs 3
+    let board = Board::parse("\n\n\n\n\n\n\n       d", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    let words = result.words.values().flat_map(|list| list.to_vec()).collect::<Vec<_>>();
+    assert!(words.contains(&"bad"));
+    assert!(!words.contains(&"abcd"));
+    for (a_move, _) in &result.score {
+        assert!(a_move.tiles_placed() <= 2, "{:?}", a_move);
+    }
+}
+
+#[test]
+fn solver_context_between_solves() {
+    let rules = || crate::test_utils::rules(&["at", "act", "cat", "tact"]);
+    
+    let tray = TrayRemaining::parse("act*", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let mut context = SolverContext::new(rules());
+    assert_eq!(
+        context.solve(&tray, &board, &RulesOverrides::default()),
+        arenas.evaluate(&tray, &board, rules()).into_owned(),
+    );
+    
+    // the tasks do not change the result
+    context.placements_per_task = 1000;
+    assert_eq!(
+        context.solve(&tray, &board, &RulesOverrides::default()),
+        arenas.evaluate(&tray, &board, rules()).into_owned(),
+    );
+    
+    context.rules.min_main_word_len = 3;
+    let result = context.solve(&tray, &board, &RulesOverrides::default());
+    assert!(!result.score.is_empty());
+    assert!(result.words.values().flatten().all(|word| word.len() >= 3));
+    
+    // the overrides only last for their solve
+    let overrides = RulesOverrides { min_main_word_len: Some(4), ..RulesOverrides::default() };
+    let overridden = context.solve(&tray, &board, &overrides);
+    assert!(!overridden.score.is_empty());
+    assert!(overridden.words.values().flatten().all(|word| word.len() >= 4));
+    assert_eq!(context.solve(&tray, &board, &RulesOverrides::default()), result);
+}
+
+#[test]
+fn moves_below_min_score() {
+    let rules = |min_score| Rules {
+        min_score,
+        ..crate::test_utils::rules(&["at", "act", "cat", "tact"])
+    };
+    
+    let tray = TrayRemaining::parse("act*", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let context = SolverContext::new(rules(0));
+    let all = context.solve(&tray, &board, &RulesOverrides::default());
+    let context = SolverContext::new(rules(10));
+    let above = context.solve(&tray, &board, &RulesOverrides::default());
+    
+    assert!(!above.score.is_empty());
+    assert_eq!(above.score, all.score.into_iter().filter(|(_, score)| *score >= 10).collect::<Vec<_>>());
+}
+
+#[test]
+fn alternatives_of_wildcards() {
+    let rules = || crate::test_utils::rules(&["tat", "tatt", "at"]);
+    
+    let t = LetterTile::Letter(Letter(b't'));
+    let a = LetterTile::Letter(Letter(b'a'));
+    let place = Placement(Position { row: 7, col: 7 }, Direction::Horizontal);
+    let board = Board::empty();
+    
+    let a_move = Move::MultiLetters(place, LetterTile::Wildcard, &[(0, a), (0, t)]);
+    assert_eq!(wildcard_alternatives(&board, rules(), &a_move, "tat"), [vec![0], vec![2]]);
+    let a_move = Move::MultiLetters(place, LetterTile::Wildcard, &[(0, a), (0, t), (0, LetterTile::Wildcard)]);
+    assert_eq!(wildcard_alternatives(&board, rules(), &a_move, "tatt"), [vec![0, 2], vec![0, 3], vec![2, 3]]);
+    assert_eq!(wildcard_alternatives(&board, rules(), &a_move, "ta"), Vec::<Vec<usize>>::new());
+    
+    // the 'a' below the second 't' makes "ta" that is not a word
+    let mut board = Board::empty();
+    board.letter_table.set(Position { row: 8, col: 9 }, Square::Filled(a));
+    let a_move = Move::MultiLetters(place, LetterTile::Wildcard, &[(0, a), (0, t)]);
+    assert_eq!(wildcard_alternatives(&board, rules(), &a_move, "tat"), [vec![0]]);
+    
+    // downwards in the last column of a board of 11 columns
+    let board = Board::with_size(15, 11);
+    let place = Placement(Position { row: 7, col: 10 }, Direction::Vertical);
+    let a_move = Move::MultiLetters(place, LetterTile::Wildcard, &[(0, a), (0, t)]);
+    assert_eq!(wildcard_alternatives(&board, rules(), &a_move, "tat"), [vec![0], vec![2]]);
+}
+
+#[test]
+fn anchors() {
+    let a = Square::Filled(LetterTile::Letter(Letter(b'a')));
+    
+    let mut board = Board::empty();
+    assert_eq!(anchor_squares(&board), [Position { row: 7, col: 7 }]);
+    
+    board.letter_table.set(Position { row: 0, col: 0 }, a);
+    assert_eq!(anchor_squares(&board), [Position { row: 0, col: 1 }, Position { row: 1, col: 0 }]);
+    
+    // a word in the middle, and a tile touching it
+    let mut board = Board::empty();
+    board.letter_table.set(Position { row: 7, col: 7 }, a);
+    board.letter_table.set(Position { row: 7, col: 8 }, a);
+    board.letter_table.set(Position { row: 8, col: 8 }, a);
+    assert_eq!(anchor_squares(&board), [
+        Position { row: 6, col: 7 },
+        Position { row: 6, col: 8 },
+        Position { row: 7, col: 6 },
+        Position { row: 7, col: 9 },
+        Position { row: 8, col: 7 },
+        Position { row: 8, col: 9 },
+        Position { row: 9, col: 8 },
+    ]);
+}
+
+#[test]
+fn equivalent_moves() {
+    let rules = crate::test_utils::rules(&["at", "act"]);
+    
+    let tray = TrayRemaining::parse("act", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    let groups = result.dedup_equivalent();
+    
+    // "at" is played at 2 places in both directions, all through the center
+    let at = groups.iter().find(|(a_move, _, _)| result.words.get(a_move).unwrap().to_vec() == ["at"]).unwrap();
+    assert_eq!((at.1, at.2), (4, 4));
+    assert_eq!(groups.iter().map(|(_, _, n)| n).sum::<usize>(), result.score.len());
+}
+
+#[test]
+fn value_of_exchange() {
+    let tray = TrayRemaining::parse("abcdefg", crate::Strictness::Strict).unwrap();
+    let keep = TrayRemaining::parse("ae", crate::Strictness::Strict).unwrap();
+    let n_e = |tray: &TrayRemaining| tray.tiles().filter(|&tile| tile == LetterTile::Letter(Letter(b'e'))).count() as f64;
+    
+    // 5 tiles are drawn from a bag that only has e
+    let bag = TrayRemaining::parse("eeeeeeee", crate::Strictness::Strict).unwrap();
+    assert_eq!(exchange_value(&tray, &bag, &keep, n_e, 0, 10), 6.0);
+    
+    // the bag does not have enough tiles to fill the tray
+    let bag = TrayRemaining::parse("ee", crate::Strictness::Strict).unwrap();
+    assert_eq!(exchange_value(&tray, &bag, &keep, n_e, 0, 10), 3.0);
+    
+    let bag = TrayRemaining::parse("eeeezzzzzzzz", crate::Strictness::Strict).unwrap();
+    let value = exchange_value(&tray, &bag, &keep, n_e, 42, 1000);
+    assert_eq!(value, exchange_value(&tray, &bag, &keep, n_e, 42, 1000));
+    // 5 of the 12 tiles are drawn, so 5/3 e on average
+    assert!((value - (1.0 + 5.0 / 3.0)).abs() < 0.2, "{}", value);
+}
+
+#[test]
+fn result_contains_move() {
+    let rules = crate::test_utils::rules(&["at"]);
+    
+    let mut board = Board::empty();
+    board.letter_table.set(Position { row: 7, col: 7 }, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+    board.value_table.set(Position { row: 7, col: 7 }, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+    let tray = TrayRemaining::parse("t", crate::Strictness::Strict).unwrap();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    
+    let t = LetterTile::Letter(Letter(b't'));
+    let place = Placement(Position { row: 7, col: 8 }, Direction::Horizontal);
+    // "at" can also be played vertically
+    assert_eq!(result.score.len(), 2);
+    let score = result.score.iter().find(|(a_move, _)| a_move.first_position() == place.0).unwrap().1;
+    assert_eq!(result.contains(&Move::MultiLetters(place, t, &[])), Some(score));
+    assert_eq!(result.contains(&Move::SingleLetter(place.0, t)), Some(score));
+    assert_eq!(result.contains(&Move::SingleLetter(Position { row: 7, col: 6 }, t)), None);
+}
+
+#[test]
+fn rectangular_board() {
+    let rules = || crate::test_utils::rules(&["at", "ta"]);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let positions = |result: EvaluationResult| {
+        let mut positions = result.score.iter().map(|(a_move, _)| a_move.positions().collect::<Vec<_>>()).collect::<Vec<_>>();
+        positions.sort_unstable_by_key(|positions| positions.iter().map(|pos| (pos.row, pos.col)).collect::<Vec<_>>());
+        positions
+    };
+    let pos = |row, col| Position { row, col };
+    
+    // 15 rows of 11 squares, the center is the one of the 8th row
+    let board = Board::with_size(15, 11);
+    let tray = TrayRemaining::parse("at", crate::Strictness::Strict).unwrap();
+    let expected = vec![
+        vec![pos(6, 5), pos(7, 5)],
+        vec![pos(7, 4), pos(7, 5)],
+        vec![pos(7, 5), pos(7, 6)],
+        vec![pos(7, 5), pos(8, 5)],
+    ];
+    // both words can be played at each place
+    let found = positions(arenas.evaluate(&tray, &board, rules()));
+    assert_eq!(found.iter().step_by(2).cloned().collect::<Vec<_>>(), expected);
+    let found = positions(evaluate_lazy(&arenas.strs, &arenas.str_lists, &arenas.moves, &tray, &board, rules()));
+    assert_eq!(found.iter().step_by(2).cloned().collect::<Vec<_>>(), expected);
+    
+    // a tile on the last column and on the last row
+    let mut board = Board::with_size(15, 11);
+    for position in [pos(3, 10), pos(14, 3)] {
+        board.letter_table.set(position, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+        board.value_table.set(position, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+    }
+    let tray = TrayRemaining::parse("t", crate::Strictness::Strict).unwrap();
+    let expected = vec![
+        vec![pos(2, 10)],
+        vec![pos(3, 9)],
+        vec![pos(4, 10)],
+        vec![pos(13, 3)],
+        vec![pos(14, 2)],
+        vec![pos(14, 4)],
+    ];
+    assert_eq!(positions(arenas.evaluate(&tray, &board, rules())), expected);
+    assert_eq!(positions(evaluate_lazy(&arenas.strs, &arenas.str_lists, &arenas.moves, &tray, &board, rules())), expected);
+}
+
+#[test]
+fn visited_moves() {
+    use std::sync::Mutex;
+    
+    let rules = || crate::test_utils::rules(&["at", "act", "cat", "tact"]);
+    
+    let tray = TrayRemaining::parse("act*", crate::Strictness::Strict).unwrap();
+    let board = Board::empty();
+    
+    let visited = Mutex::new(vec![]);
+    evaluate_visit(&tray, &board, rules(), |a_move, score| visited.lock().unwrap().push((OwnedMove::from(a_move), score)));
+    let mut visited = visited.into_inner().unwrap();
+    visited.sort_unstable_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+    
+    let expected = SolverContext::new(rules()).solve(&tray, &board, &RulesOverrides::default());
+    assert_eq!(visited, expected.score);
+}
+
+#[test]
+fn exposed_word_bonuses() {
+    use crate::score_rules::ScrabbleBonus;
+    
+    let others = [(0, LetterTile::Letter(Letter(b'a'))), (0, LetterTile::Letter(Letter(b't')))];
+    let cat = Move::MultiLetters(
+        Placement(Position { row: 7, col: 6 }, Direction::Horizontal),
+        LetterTile::Letter(Letter(b'c')),
+        &others,
+    );
+    let mut board = Board::empty();
+    for (pos, tile) in cat.positions().zip(cat.placed_tiles()) {
+        board.letter_table.set(pos, Square::Filled(tile));
+    }
+    // the four triple word squares in the middle of the sides
+    assert_eq!(exposure_penalty(&board, &cat, &ScrabbleBonus), 8.);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(
+        &TrayRemaining::parse("cat", crate::Strictness::Strict).unwrap(), &Board::empty(),
+        crate::test_utils::rules(&["cat"]),
+    );
+    let ranked = rank_by_exposure(&result, &Board::empty(), &ScrabbleBonus, 0.5);
+    assert_eq!(ranked.len(), result.score.len());
+    assert!(ranked.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    let (_, value) = ranked.iter().find(|(a_move, _)| *a_move == cat).unwrap();
+    assert_eq!(*value, result.contains(&cat).unwrap() as f64 - 4.);
+}
+
+#[test]
+fn sample_of_lengths() {
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(
+        &TrayRemaining::parse("cats", crate::Strictness::Strict).unwrap(), &Board::empty(),
+        crate::test_utils::rules(&["at", "cat", "act", "cats", "scat"]),
+    );
+    
+    let counts = vec![(2, 1), (3, 2), (5, 4)].into_iter().collect();
+    let sample = result.sample_by_length(&counts);
+    let lens = result.words.iter().map(|(a_move, list)| (a_move.clone(), list.to_vec()[0].len())).collect::<HashMap<_, _>>();
+    let len = |a_move| lens[a_move];
+    
+    assert_eq!(sample.iter().filter(|(a_move, _)| len(a_move) == 2).count(), 1);
+    assert_eq!(sample.iter().filter(|(a_move, _)| len(a_move) == 3).count(), 2);
+    assert_eq!(sample.len(), 3);
+    assert!(sample.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    
+    // the best moves of each length are kept
+    let best_of_3 = result.score.iter().filter(|(a_move, _)| len(a_move) == 3).map(|(_, score)| *score).max().unwrap();
+    assert!(sample.iter().any(|(a_move, score)| len(a_move) == 3 && *score == best_of_3));
+}
+
+#[test]
+fn degenerate_opening_racks() {
+    let moves = |tray| {
+        let arenas = crate::test_utils::TestArenas::default();
+        
+        arenas.evaluate(
+            &TrayRemaining::parse(tray, crate::Strictness::Strict).unwrap(), &Board::empty(),
+            crate::test_utils::rules(&["aa", "brr", "eau", "hmm", "shh"]),
+        ).into_owned()
+    };
+    
+    // no word at all: nothing to play, only an exchange or a pass
+    assert!(moves("bcdfgjk").score.is_empty());
+    assert!(moves("iiouuoi").score.is_empty());
+    assert!(moves("").score.is_empty());
+    assert!(moves("b").score.is_empty());
+    
+    // the rare words without vowels, or with vowels only, through the center
+    let center = Position { row: 7, col: 7 };
+    let consonants = moves("bcdfrrk");
+    assert!(!consonants.score.is_empty());
+    assert!(consonants.words.values().all(|words| words == &["brr"]));
+    assert!(consonants.score.iter().all(|(a_move, _)| a_move.as_move().positions().any(|pos| pos == center)));
+    
+    let vowels = moves("aeiouau");
+    assert!(vowels.words.values().flatten().any(|word| word == "eau"));
+    assert!(vowels.words.values().flatten().any(|word| word == "aa"));
+}
+
+#[test]
+fn rendered_cross_checks() {
+    let mut board = Board::with_size(3, 4);
+    board.letter_table.set(Position { row: 1, col: 1 }, Square::Filled(LetterTile::Letter(Letter(b'a'))));
+    let dictionary = crate::build_dictionary(vec!["at", "ta", "ma"]).unwrap();
+    
+    let checks = cross_checks(&board, Direction::Horizontal, &dictionary, &HashSet::new());
+    assert_eq!(render_cross_checks(&checks), concat!(
+        ".  mt .  .\n",
+        ".  -  .  .\n",
+        ".  t  .  .\n",
+    ));
+    
+    let checks = cross_checks(&board, Direction::Vertical, &dictionary, &HashSet::new());
+    assert_eq!(render_cross_checks(&checks), concat!(
+        ".  .  .  .\n",
+        "mt -  t  .\n",
+        ".  .  .  .\n",
+    ));
+    assert_eq!(render_board_cross_checks(&board, &checks), concat!(
+        ".  .  .  .\n",
+        "mt #  t  .\n",
+        ".  .  .  .\n",
+    ));
+}
+
+#[test]
+fn best_of_each_direction() {
+    let rules = || crate::test_utils::rules(&["at", "ta", "bo", "zax"]);
+    
+    // "zax" fits only horizontally across the `a` (of "ta"), "bo" only vertically above the `o`
+    let board = Board::parse("\n\no\n\n\n\n       t\n       a\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("bzx", crate::Strictness::Strict).unwrap();
+    let (horizontal, vertical) = best_per_direction(&tray, &board, rules());
+    
+    let (horizontal, _) = horizontal.unwrap();
+    assert!(matches!(horizontal, OwnedMove::MultiLetters(Placement(_, Direction::Horizontal), _, _)));
+    assert_eq!(Some(horizontal), best_move(&tray, &board, rules()).map(|(a_move, _)| a_move));
+    assert!(matches!(vertical, Some((OwnedMove::SingleLetter(Position { row: 1, col: 0 }, _), _))));
+    
+    // no move at all
+    let tray = TrayRemaining::parse("q", crate::Strictness::Strict).unwrap();
+    assert_eq!(best_per_direction(&tray, &board, rules()), (None, None));
+}
+
+#[test]
+fn words_longer_than_the_board() {
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let board = Board::parse("\n\n\n\n\n\n\nabcdefghijklmn\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let result = arenas.evaluate(
+        &TrayRemaining::parse("op*", crate::Strictness::Strict).unwrap(), &board,
+        crate::test_utils::rules(&["abcdefghijklmno", "abcdefghijklmnop", "abcdefghijklmnopqrstuvwxyz"]),
+    ).into_owned();
+    
+    // only the word of 15 letters fits on the line, the longer ones are never matched
+    let words = result.words.values().flatten().collect::<HashSet<_>>();
+    assert_eq!(words, ["abcdefghijklmno".to_owned()].iter().collect());
+    assert!(result.score.iter().all(|(a_move, _)| a_move.as_move().positions().eq([Position { row: 7, col: 14 }])));
+}
+
+#[test]
+fn one_tile_extensions() {
+    let rules = Rules {
+        exclude: vec!["cat".to_string()].into_iter().collect(),
+        ..crate::test_utils::rules(&["at", "ate", "bat", "cat", "eat", "hat"])
+    };
+    
+    // "ate" would form "oe" downwards, and "cat" is excluded
+    let board = Board::parse("\n\n\n\n\n\n         o\n       at\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("ehc*", crate::Strictness::Strict).unwrap();
+    let before = Position { row: 7, col: 6 };
+    assert_eq!(
+        extension_scores(&board, Placement(Position { row: 7, col: 8 }, Direction::Horizontal), &tray, rules),
+        vec![(before, Letter(b'b'), 2), (before, Letter(b'e'), 3), (before, Letter(b'h'), 6)],
+    );
+}
+
+#[test]
+fn free_placements() {
+    let rules = |require_attachment| Rules { require_attachment, ..crate::test_utils::rules(&["at", "ta"]) };
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let tile = |l| LetterTile::Letter(Letter(l));
+    let corner = Move::MultiLetters(Placement(Position { row: 0, col: 0 }, Direction::Horizontal), tile(b'a'), &[(0, tile(b't'))]);
+    let tray = TrayRemaining::parse("at", crate::Strictness::Strict).unwrap();
+    
+    for board in &["", "\n\n\n\n\n\n\n       a\n"] {
+        let board = Board::parse(board, &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+        
+        let attached = arenas.evaluate(&tray, &board, rules(true));
+        assert_eq!(attached.contains(&corner), None);
+        
+        let free = arenas.evaluate(&tray, &board, rules(false));
+        // on the triple word square
+        assert_eq!(free.contains(&corner), Some(6));
+        assert!(free.score.len() > attached.score.len());
+        // the attached moves are still there
+        for (a_move, score) in &attached.score {
+            assert_eq!(free.contains(a_move), Some(*score));
+        }
+    }
+}
+
+#[cfg(feature = "profile")]
+#[test]
+fn automaton_stats() {
+    let rules = crate::test_utils::rules(&["at", "ta", "tat"]);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let tray = TrayRemaining::parse("at", crate::Strictness::Strict).unwrap();
+    let result = arenas.evaluate(&tray, &Board::empty(), rules);
+    
+    // "at" and "ta", horizontally and vertically, from two placements each
+    assert_eq!(result.score.len(), 8);
+    assert_eq!(result.stats.matches, 8);
+    assert!(result.stats.transitions >= 2 * result.stats.matches);
+    assert!(result.stats.can_match_checks > 0);
+}
+
+#[test]
+fn merged_results() {
+    let rules = |words: Vec<&str>| crate::test_utils::rules(&words);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    // with a wildcard of any meaning on the board, the a after it plays both "ta" and "ea"
+    let board = Board::parse("\n\n\n\n\n\n\n       *\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("ae", crate::Strictness::Strict).unwrap();
+    
+    let first = arenas.evaluate(&tray, &board, rules(vec!["ta", "eta"]));
+    let second = arenas.evaluate(&tray, &board, rules(vec!["ea", "ta", "te"]));
+    let merged = first.merge(second, &arenas.str_lists).into_owned();
+    
+    let expected = arenas.evaluate(&tray, &board, rules(vec!["ea", "eta", "ta", "te"])).into_owned();
+    assert_eq!(merged.score, expected.score);
+    assert_eq!(merged.words, expected.words);
+    let a_after = OwnedMove::SingleLetter(Position { row: 7, col: 8 }, LetterTile::Letter(Letter(b'a')));
+    assert_eq!(merged.words[&a_after], vec!["ea", "ta"]);
+}
+
+#[test]
+fn nth_best_moves() {
+    let rules = crate::test_utils::rules(&["at", "ta", "tat"]);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let board = Board::parse("\n\n\n\n\n\n\n       a\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("tt", crate::Strictness::Strict).unwrap();
+    let result = arenas.evaluate(&tray, &board, rules);
+    
+    let n = result.score.len();
+    assert!(n > 2);
+    assert_eq!(result.nth_best(1), result.score.last());
+    assert_eq!(result.nth_best(2), result.score.get(n - 2));
+    assert_eq!(result.nth_best(n), result.score.first());
+    assert_eq!(result.nth_best(0), None);
+    assert_eq!(result.nth_best(n + 1), None);
+}
+
+#[test]
+fn safe_moves() {
+    let rules = crate::test_utils::rules(&["ae", "at", "ta", "tae", "te"]);
+    let strict = crate::build_dictionary(vec!["ae", "at", "ta"]).unwrap();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let board = Board::parse("\n\n\n\n\n\n\n       t\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("ae", crate::Strictness::Strict).unwrap();
+    let result = arenas.evaluate(&tray, &board, rules);
+    
+    let marked = result.mark_safe(&board, &strict);
+    assert_eq!(marked.iter().map(|(a_move, score, _)| (a_move.clone(), *score)).collect::<Vec<_>>(), result.score);
+    let safe = marked.iter().map(|(a_move, _, safe)| (OwnedMove::from(a_move), *safe)).collect::<HashMap<_, _>>();
+    
+    let tile = |l| LetterTile::Letter(Letter(l));
+    let vertical_ae = |row| OwnedMove::MultiLetters(Placement(Position { row, col: 8 }, Direction::Vertical), tile(b'a'), vec![(0, tile(b'e'))]);
+    assert!(safe[&OwnedMove::SingleLetter(Position { row: 7, col: 6 }, tile(b'a'))]);
+    // "tae" is not in the strict list
+    assert!(!safe[&OwnedMove::MultiLetters(Placement(Position { row: 7, col: 8 }, Direction::Horizontal), tile(b'a'), vec![(0, tile(b'e'))])]);
+    // "ae" forms "ta" with the tile of the board, but "te" when the e is next to it
+    assert!(safe[&vertical_ae(7)]);
+    assert!(!safe[&vertical_ae(6)]);
+}
+
+#[test]
+fn equity_of_moves() {
+    let leave = |tray| leave_value(&TrayRemaining::parse(tray, crate::Strictness::Strict).unwrap());
+    assert_eq!(leave(""), 0.);
+    assert_eq!(leave("s*"), 8. + 25.);
+    // a second e, and 3 vowels for no consonant
+    assert_eq!(leave("eea"), 1.5 * 2. - 3. + 1. - 2. * 2.);
+    
+    let rules = crate::test_utils::rules(&["ab", "sab"]);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let tray = TrayRemaining::parse("sab*", crate::Strictness::Strict).unwrap();
+    let result = arenas.evaluate(&tray, &Board::empty(), rules);
+    
+    // "sab" scores more, but "ab" keeps the s with the wildcard
+    let (best_by_score, _) = result.score.last().unwrap();
+    assert_eq!(result.words.get(best_by_score).unwrap().to_vec(), ["sab"]);
+    let ranked = rank_by_equity(&result, &tray, leave_value);
+    assert_eq!(ranked.len(), result.score.len());
+    let (best, score, equity) = ranked.last().unwrap();
+    assert_eq!(result.words.get(best).unwrap().to_vec(), ["ab"]);
+    assert_eq!((*score, *equity), (8, 8. + leave("s*")));
+    assert!(ranked.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+}
+
+#[test]
+fn words_of_board_and_tray() {
+    let dictionary = crate::build_dictionary(vec!["acts", "at", "cast", "cats", "catsup", "dog", "scat", "taco"]).unwrap();
+    let board = Board::parse("\n\n\n\n\n\n\n      cat\n", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("s*", crate::Strictness::Strict).unwrap();
+    
+    // the wildcard is the o of "taco", but "dog" and "catsup" miss two letters
+    assert_eq!(words_from_all_tiles(&board, &tray, &dictionary), ["acts", "cast", "cats", "scat", "taco", "at"]);
+    
+    let tray = TrayRemaining::parse("", crate::Strictness::Strict).unwrap();
+    assert_eq!(words_from_all_tiles(&board, &tray, &dictionary), ["at"]);
+}
+
+#[test]
+fn words_one_tile_short() {
+    let rules = crate::test_utils::rules(&["cats", "chat", "scat"]);
+    let board = Board::parse("\n\n\n\n\n\n\n       at", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("c", crate::Strictness::Strict).unwrap();
+    
+    assert_eq!(near_miss_words(&board, &tray, &rules, 0), []);
+    assert_eq!(near_miss_words(&board, &tray, &rules, 1), [
+        ("cats".to_owned(), vec![Letter(b's')]),
+        ("chat".to_owned(), vec![Letter(b'h')]),
+        ("scat".to_owned(), vec![Letter(b's')]),
+    ]);
+    // played downward through the "a", "cats" misses both its "t" and its "s"
+    assert!(near_miss_words(&board, &tray, &rules, 2).contains(&("cats".to_owned(), vec![Letter(b's'), Letter(b't')])));
+}
+
+#[test]
+fn heatmap_of_other_sizes() {
+    let rules = crate::test_utils::rules(&["at", "cat"]);
+    let board = Board::with_size(15, 11);
+    let tray = TrayRemaining::parse("cat", crate::Strictness::Strict).unwrap();
+    
+    let heatmap = score_heatmap(&tray, &board, rules);
+    assert_eq!((heatmap.len_in(Direction::Vertical), heatmap.len_in(Direction::Horizontal)), (15, 11));
+    assert!(heatmap.iter_positions().any(|(_, &score)| score > 0));
+}
+
+#[test]
+fn square_values() {
+    use crate::Position;
+    
+    let rules = crate::test_utils::rules(&["at", "cat"]);
+    let board = Board::parse("\n\n\n\n\n\n\n       at", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("c", crate::Strictness::Strict).unwrap();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray, &board, rules);
+    let cat_score = result.score.iter().map(|(_, score)| *score).max().unwrap();
+    assert_eq!(result.score.len(), 1);
+    
+    let values = square_value_map(&result, &board);
+    for col in 0..15 {
+        let expected = if (6..9).contains(&col) { cat_score as f64 } else { 0. };
+        assert_eq!(values.get(Position { row: 7, col }), Some(&expected), "{}", col);
+    }
+    assert_eq!(values.iter_positions().map(|(_, value)| value).sum::<f64>(), 3. * cat_score as f64);
+    
+    // the map has the dimensions of the board
+    let values = square_value_map(&result, &Board::with_size(15, 11));
+    assert_eq!((values.len_in(Direction::Vertical), values.len_in(Direction::Horizontal)), (15, 11));
+}
+
+#[test]
+fn moves_in_region() {
+    use crate::Position;
+    
+    let rules = |region| Rules { region, ..crate::test_utils::rules(&["act", "at", "cat", "ta"]) };
+    let board = Board::parse("", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("cat", crate::Strictness::Strict).unwrap();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    // the part of the board above and on the left of the center, which it includes
+    let region = (Position { row: 0, col: 0 }, Position { row: 7, col: 7 });
+    let all = arenas.evaluate(&tray, &board, rules(None));
+    let result = arenas.evaluate(&tray, &board, rules(Some(region)));
+    
+    // the words end on the center
+    assert_eq!(result.score.len(), 8);
+    assert!(result.score.len() < all.score.len());
+    for (a_move, _) in &result.score {
+        for pos in a_move.footprint(&board, true) {
+            assert!(pos.row <= 7 && pos.col <= 7, "{:?}", a_move);
+        }
+    }
+}
+
+#[test]
+fn value_of_premium_square() {
+    let rules = || crate::test_utils::rules(&["cat"]);
+    let board = Board::parse("", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("cat", crate::Strictness::Strict).unwrap();
+    
+    // "cat" is always played on the double word of the center, for 12 instead of 6
+    assert_eq!(premium_square_value(&tray, &board, rules(), Position { row: 7, col: 7 }), 6);
+    assert_eq!(premium_square_value(&tray, &board, rules(), Position { row: 0, col: 0 }), 0);
+}
+
+#[test]
+fn stated_first_move() {
+    let rules = |first_move| Rules { first_move, ..crate::test_utils::rules(&["at", "ta"]) };
+    let empty = Board::parse("", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let corner = Board::parse("a", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("at", crate::Strictness::Strict).unwrap();
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    let n_moves = |board, first_move| arenas.evaluate(&tray, board, rules(first_move)).score.len();
+    
+    // "at" and "ta" from both squares of the center, in both directions
+    assert_eq!(n_moves(&empty, None), 8);
+    assert_eq!(n_moves(&empty, Some(true)), 8);
+    // nothing to attach to in the middle of a game
+    assert_eq!(n_moves(&empty, Some(false)), 0);
+    
+    // a "t" after or below the "a" of the corner, alone or as the start of "ta"
+    assert_eq!(n_moves(&corner, None), 2 + 2);
+    assert_eq!(n_moves(&corner, Some(true)), 2 + 2 + 8);
+}
+
+#[test]
+fn held_tiles_not_played() {
+    let rules = crate::test_utils::rules(&["as", "at", "cat", "cats", "scat"]);
+    let board = Board::parse("", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("cats*", crate::Strictness::Strict).unwrap();
+    // the "x" is not in the tray
+    let held = TrayRemaining::parse("s*x", crate::Strictness::Strict).unwrap();
+    assert_eq!(tray.without(&held).tiles().collect::<Vec<_>>(), TrayRemaining::parse("cat", crate::Strictness::Strict).unwrap().tiles().collect::<Vec<_>>());
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let result = arenas.evaluate(&tray.without(&held), &board, rules);
+    let words = result.words.values().flat_map(|list| list.to_vec()).collect::<HashSet<_>>();
+    assert_eq!(words, ["at", "cat"].iter().copied().collect());
+    for (a_move, _) in &result.score {
+        assert!(a_move.placed_tiles().all(|tile| tile != LetterTile::Letter(Letter(b's')) && tile != LetterTile::Wildcard), "{:?}", a_move);
+    }
+}
+
+#[test]
+fn plays_of_two_letters() {
+    let rules = crate::test_utils::rules(&["at", "cat", "cats", "ta", "tat"]);
+    let board = Board::parse("\n\n\n\n\n\n\n      cat", &crate::BoardFormat::default(), crate::Strictness::Strict).unwrap();
+    let tray = TrayRemaining::parse("ast", crate::Strictness::Strict).unwrap();
+    
+    let plays = two_letter_plays(&board, &tray, &rules);
+    assert_eq!(plays.len(), 8);
+    for (a_move, _, words) in &plays {
+        assert_eq!(words[0].len(), 2, "{:?}", a_move);
+    }
+    // the best ones play "ta" above or below the "at" of "cat", forming two more words
+    let (_, best_score, best_words) = plays.last().unwrap();
+    assert_eq!(*best_score, 8);
+    assert_eq!(best_words.len(), 3);
+    // a single "t" below the "a" only forms "at"
+    assert!(plays.iter().any(|(_, score, words)| *score == 2 && words == &["at"]));
+    
+    // the same as the moves of `evaluate` playing a two-letter word
+    let arenas = crate::test_utils::TestArenas::default();
+    let result = arenas.evaluate(&tray, &board, rules);
+    let mut twos = result.score.iter()
+        .filter(|(a_move, _)| result.words.get(a_move).unwrap().to_vec().iter().any(|word| word.len() == 2))
+        .map(|(a_move, score)| (OwnedMove::from(a_move), *score))
+        .collect::<Vec<_>>();
+    twos.sort();
+    let mut plays = plays.into_iter().map(|(a_move, score, _)| (a_move, score)).collect::<Vec<_>>();
+    plays.sort();
+    assert_eq!(plays, twos);
+    
+    // the two bytes of an accented letter, or other characters, in the dictionary
+    let dictionary = Set::from_iter(vec!["a'", "at", "ta", "\u{e9}"]).unwrap();
+    let rules = Rules::new(dictionary, crate::test_utils::score_rules());
+    assert_eq!(two_letter_plays(&board, &tray, &rules).len(), 8);
+}
+
+#[test]
+fn equity_ties_by_exposure() {
+    use crate::score_rules::ScrabbleBonus;
+    
+    let rules = crate::test_utils::rules(&["at"]);
+    
+    let arenas = crate::test_utils::TestArenas::default();
+    
+    let board = Board::empty();
+    let tray = TrayRemaining::parse("at", crate::Strictness::Strict).unwrap();
+    let result = arenas.evaluate(&tray, &board, rules);
+    // "at" through the center has the same score and leave from its 4 places
+    assert_eq!(result.score.len(), 4);
+    
+    let exposure = |a_move: &Move| exposure_penalty(&board_after(&board, a_move), a_move, &ScrabbleBonus);
+    let ranked = rank_by_equity_and_exposure(&result, &board, &tray, leave_value, &ScrabbleBonus, 1.);
+    assert!(ranked.windows(2).all(|pair| exposure(&pair[0].0) >= exposure(&pair[1].0)));
+    
+    // without the exposure, the order of the moves breaks the ties
+    let ranked = rank_by_equity_and_exposure(&result, &board, &tray, leave_value, &ScrabbleBonus, 0.);
+    assert!(ranked.windows(2).all(|pair| pair[0].0 < pair[1].0));
+}
+
+#[test]
+fn score_of_blank_assignment() {
+    let rules = || crate::test_utils::rules(&["tat"]);
+    
+    let t = LetterTile::Letter(Letter(b't'));
+    let a = LetterTile::Letter(Letter(b'a'));
+    // the first 't' is on a double letter square
+    let place = Placement(Position { row: 7, col: 3 }, Direction::Horizontal);
+    let board = Board::empty();
+    let a_move = Move::MultiLetters(place, LetterTile::Wildcard, &[(0, a), (0, t)]);
+    
+    let scores = wildcard_alternatives(&board, rules(), &a_move, "tat").iter()
+    .map(|blanks| score_with_blank_at(&board, &a_move, "tat", blanks, &rules()))
+    .collect::<Vec<_>>();
+    assert_eq!(scores, [Some(2), Some(3)]);
+    assert_eq!(score_with_blank_at(&board, &a_move, "tat", &[], &rules()), Some(4));
+    assert_eq!(score_with_blank_at(&board, &a_move, "tat", &[3], &rules()), None);
+    assert_eq!(score_with_blank_at(&board, &a_move, "ta", &[0], &rules()), None);
 }
@@ -1,25 +1,40 @@
 use super::Letter;
+use crate::Alphabet;
 
+/// The number of letters a `LetterSet` can hold, one per value of `Letter`
+///
+/// The letters are the bytes of the words in the dictionary (the fst is browsed byte
+/// by byte), so a tile that is not a single byte (like a digraph) would need its own
+/// byte in the dictionary, among these 256 values
+pub const LETTER_COUNT: usize = u8::MAX as usize + 1;
+
+const BLOCKS: usize = LETTER_COUNT / 128;
+
+/// A set of letters, as one bit per possible letter
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct LetterSet {
     // bit is one if letter is in it
-    accepted: [u128; 2],
+    accepted: [u128; BLOCKS],
 }
 
 impl LetterSet {
     pub fn empty() -> Self {
-        Self { accepted: [0; 2] }
+        Self { accepted: [0; BLOCKS] }
     }
     pub fn any() -> Self {
-        Self { accepted: [u128::MAX; 2] }
+        Self { accepted: [u128::MAX; BLOCKS] }
     }
-    pub fn contains(&self, letter: Letter) -> bool {
+    const fn bit(letter: Letter) -> (usize, u128) {
         let i = letter.0 as usize;
-        (self.accepted[i / 128]  & (1 << (i%128))) != 0
+        (i / 128, 1 << (i % 128))
+    }
+    pub fn contains(&self, letter: Letter) -> bool {
+        let (block, bit) = Self::bit(letter);
+        (self.accepted[block] & bit) != 0
     }
     pub fn insert(&mut self, letter: Letter) {
-        let i = letter.0 as usize;
-        self.accepted[i / 128]  |= 1 << (i%128)
+        let (block, bit) = Self::bit(letter);
+        self.accepted[block] |= bit
     }
     pub fn from_many(iter: impl Iterator<Item=Letter>) -> Self {
         let mut tmp = Self::empty();
@@ -33,11 +48,29 @@ impl LetterSet {
         self.accepted.iter().all(|&l| l == u128::MAX)
     }
     
+    /// The number of letters of the set before `letter`, None if it is not in the set
+    pub fn index_of(&self, letter: Letter) -> Option<usize> {
+        if !self.contains(letter) {
+            return None
+        }
+        let (block, bit) = Self::bit(letter);
+        let before = self.accepted[..block].iter().map(|b| b.count_ones()).sum::<u32>()
+            + (self.accepted[block] & (bit - 1)).count_ones();
+        Some(before as usize)
+    }
+    
+    /// The letters of the `alphabet`
+    pub fn alphabet(alphabet: &Alphabet) -> Self {
+        Self::from_many(alphabet.letters())
+    }
+    
+    /// The english letters, see `Alphabet::english`
     pub const ALPHABET: Self = {
-        let mut tmp = Self { accepted: [0; 2] };
+        let mut tmp = Self { accepted: [0; BLOCKS] };
         let mut i = b'a';
         while i <= b'z' {
-            tmp.accepted[i as usize / 128]  |= 1 << (i as usize%128);
+            let (block, bit) = Self::bit(Letter(i));
+            tmp.accepted[block] |= bit;
             i += 1;
         }
         tmp
@@ -81,7 +114,7 @@ impl fmt::Debug for LetterSet {
 fn letter_set() {
     let empty = LetterSet::empty();
     for l in 0..=255u8 {
-        assert_eq!(empty.contains(Letter(l)), false);
+        assert!(!empty.contains(Letter(l)));
     }
     assert!(empty.is_empty());
     assert!(!empty.is_any());
@@ -92,10 +125,10 @@ fn letter_set() {
         assert!(not_empty.contains(Letter(i)));
     }
     for l in 0..=255u8 {
-        if some.iter().any(|&i| i == l) {
+        if some.contains(&l) {
             continue
         }
-        assert_eq!(not_empty.contains(Letter(l)), false);
+        assert!(!not_empty.contains(Letter(l)));
     }
     assert!(!not_empty.is_empty());
     assert!(!not_empty.is_any());
@@ -107,3 +140,19 @@ fn letter_set() {
     assert!(!any.is_empty());
     assert!(any.is_any());
 }
+
+#[test]
+fn letter_set_of_alphabet() {
+    assert_eq!(LetterSet::alphabet(&Alphabet::english()), LetterSet::ALPHABET);
+    
+    let alphabet = Alphabet::new(b"ab".iter().map(|&l| Letter(l)));
+    assert_eq!(alphabet.letters().collect::<Vec<_>>(), vec![Letter(b'a'), Letter(b'b')]);
+    assert!(!LetterSet::alphabet(&alphabet).contains(Letter(b'c')));
+    
+    assert_eq!(Alphabet::english().index(Letter(b'a')), Some(0));
+    assert_eq!(Alphabet::english().index(Letter(b'z')), Some(25));
+    assert_eq!(Alphabet::english().index(Letter(b'A')), None);
+    let high: LetterSet = [Letter(3), Letter(200), Letter(250)].iter().copied().collect();
+    assert_eq!(high.index_of(Letter(250)), Some(2));
+    assert_eq!(high.index_of(Letter(201)), None);
+}
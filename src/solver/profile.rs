@@ -0,0 +1,82 @@
+
+//! Counts of what the automaton does while browsing the dictionary, with the `profile` feature
+//!
+//! Without the feature, `Counters` is empty and the automaton is not wrapped
+
+#[cfg(feature = "profile")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "profile")]
+use fst::Automaton;
+
+/// What the automaton did during a solve
+#[cfg(feature = "profile")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvaluationStats {
+    /// The letters tried (calls to `accept`)
+    pub transitions: usize,
+    /// The checks whether a word can still be found (calls to `can_match`)
+    pub can_match_checks: usize,
+    /// The states where a word is found (calls to `is_match` that are true)
+    pub matches: usize,
+}
+
+/// The counts shared by the tasks of a solve
+#[derive(Default)]
+pub(crate) struct Counters {
+    #[cfg(feature = "profile")]
+    transitions: AtomicUsize,
+    #[cfg(feature = "profile")]
+    can_match_checks: AtomicUsize,
+    #[cfg(feature = "profile")]
+    matches: AtomicUsize,
+}
+
+#[cfg(feature = "profile")]
+impl Counters {
+    pub(crate) fn stats(&self) -> EvaluationStats {
+        EvaluationStats {
+            transitions: self.transitions.load(Ordering::Relaxed),
+            can_match_checks: self.can_match_checks.load(Ordering::Relaxed),
+            matches: self.matches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// An automaton counting the calls made to `automaton`
+#[cfg(feature = "profile")]
+pub(crate) struct Counted<'c, A> {
+    pub(crate) automaton: A,
+    pub(crate) counters: &'c Counters,
+}
+
+#[cfg(feature = "profile")]
+impl<'c, A: Automaton> Automaton for Counted<'c, A> {
+    type State = A::State;
+    
+    fn start(&self) -> Self::State {
+        self.automaton.start()
+    }
+    
+    fn is_match(&self, state: &Self::State) -> bool {
+        let is_match = self.automaton.is_match(state);
+        if is_match {
+            self.counters.matches.fetch_add(1, Ordering::Relaxed);
+        }
+        is_match
+    }
+    
+    fn can_match(&self, state: &Self::State) -> bool {
+        self.counters.can_match_checks.fetch_add(1, Ordering::Relaxed);
+        self.automaton.can_match(state)
+    }
+    
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        self.automaton.will_always_match(state)
+    }
+    
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        self.counters.transitions.fetch_add(1, Ordering::Relaxed);
+        self.automaton.accept(state, byte)
+    }
+}
@@ -1,6 +1,8 @@
 
 use fst::{Automaton, Set, IntoStreamer, Streamer};
 
+use std::collections::HashSet;
+
 use super::{Square, RestrictedSquare, LetterTile, Letter, LetterSet};
 
 struct RestrictionChecker<'a> {
@@ -19,17 +21,14 @@ enum RestrictionCheckerState {
 impl<'a> Automaton for RestrictionChecker<'a> {
     type State = Option<RestrictionCheckerState>;
     fn start(&self) -> Self::State {
-        if self.prefix.len() == 0 {
+        if self.prefix.is_empty() {
             Some(RestrictionCheckerState::Mid)
         } else {
             Some(RestrictionCheckerState::Prefix(0))
         }
     }
     fn is_match(&self, state: &Self::State) -> bool {
-        match state {
-            Some(RestrictionCheckerState::Done(_)) => true,
-            _ => false,
-        }
+        matches!(state, Some(RestrictionCheckerState::Done(_)))
     }
     fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
         state.as_ref().and_then(|state| {
@@ -50,7 +49,7 @@ impl<'a> Automaton for RestrictionChecker<'a> {
                     }
                 },
                 RestrictionCheckerState::Mid => {
-                    Some(if self.suffix.len() == 0 {
+                    Some(if self.suffix.is_empty() {
                         RestrictionCheckerState::Done(Letter(byte))
                     } else {
                         RestrictionCheckerState::Suffix(0, Letter(byte))
@@ -84,7 +83,7 @@ impl<'a> Automaton for RestrictionChecker<'a> {
 
 pub fn find_restrictions(
     line: &[Square], restr: &mut [RestrictedSquare],
-    dictionary: &Set<impl AsRef<[u8]>>,
+    dictionary: &Set<impl AsRef<[u8]>>, exclude: &HashSet<String>,
 ) {
     assert_eq!(line.len(), restr.len());
     
@@ -92,52 +91,71 @@ pub fn find_restrictions(
     let mut suffix = vec![];
     
     for (i, r) in restr.iter_mut().enumerate() {
-        *r = if let Some(&tile) = line[i].tile() {
-            RestrictedSquare::Filled(tile)
+        *r = restriction_at(line, i, &mut prefix, &mut suffix, dictionary, exclude);
+    }
+}
+
+/// The restriction of the single square `i` of the `line`
+pub fn find_restriction(
+    line: &[Square], i: usize,
+    dictionary: &Set<impl AsRef<[u8]>>, exclude: &HashSet<String>,
+) -> RestrictedSquare {
+    restriction_at(line, i, &mut vec![], &mut vec![], dictionary, exclude)
+}
+
+fn restriction_at(
+    line: &[Square], i: usize,
+    prefix: &mut Vec<LetterTile>, suffix: &mut Vec<LetterTile>,
+    dictionary: &Set<impl AsRef<[u8]>>, exclude: &HashSet<String>,
+) -> RestrictedSquare {
+    if let Some(&tile) = line[i].tile() {
+        RestrictedSquare::Filled(tile)
+    } else {
+        // find prefix
+        prefix.clear();
+        for j in (0..i).rev() {
+            if let Some(&s) = line[j].tile() {
+                prefix.insert(0, s)
+            } else {
+                break
+            }
+        }
+        
+        // find suffix
+        suffix.clear();
+        for square in &line[(i+1)..] {
+            if let Some(&s) = square.tile() {
+                suffix.push(s)
+            } else {
+                break
+            }
+        }
+        
+        RestrictedSquare::Empty(if prefix.is_empty() && suffix.is_empty() {
+            // if prefix == suffix == "" then ALPHABET
+            LetterSet::any()
         } else {
-            // find prefix
-            prefix.clear();
-            for j in (0..i).rev() {
-                if let Some(&s) = line[j].tile() {
-                    prefix.insert(0, s)
-                } else {
-                    break
+            // make regex: prefix[a-z]suffix
+            let automaton = RestrictionChecker {
+                prefix: &prefix[..],
+                suffix: &suffix[..],
+            };
+            // check against dict
+            let mut matches = dictionary.search_with_state(automaton).into_stream();
+            let mut letter_set = LetterSet::empty();
+            while let Some((word, state)) = matches.next() {
+                if std::str::from_utf8(word).is_ok_and(|word| exclude.contains(word)) {
+                    continue
                 }
-            }
-            
-            // find suffix
-            suffix.clear();
-            for j in (i+1)..(line.len()) {
-                if let Some(&s) = line[j].tile() {
-                    suffix.push(s)
+                if let Some(RestrictionCheckerState::Done(l)) = state {
+                    letter_set.insert(l);
                 } else {
-                    break
+                    unreachable!("not in final state");
                 }
             }
             
-            RestrictedSquare::Empty(if prefix.is_empty() && suffix.is_empty() {
-                // if prefix == suffix == "" then ALPHABET
-                LetterSet::any()
-            } else {
-                // make regex: prefix[a-z]suffix
-                let automaton = RestrictionChecker {
-                    prefix: &prefix[..],
-                    suffix: &suffix[..],
-                };
-                // check against dict
-                let mut matches = dictionary.search_with_state(automaton).into_stream();
-                let mut letter_set = LetterSet::empty();
-                while let Some((_, state)) = matches.next() {
-                    if let Some(RestrictionCheckerState::Done(l)) = state {
-                        letter_set.insert(l);
-                    } else {
-                        unreachable!("not in final state");
-                    }
-                }
-                
-                letter_set
-            })
-        }
+            letter_set
+        })
     }
 }
 
@@ -193,7 +211,7 @@ fn test() {
 
     find_restrictions(
         &line, &mut restr,
-        &dict,
+        &dict, &HashSet::new(),
     );
 
     dbg!(&restr);
@@ -214,3 +232,23 @@ fn test() {
         RestrictedSquare::Filled(LetterTile::Letter(Letter(b'e'))),
     ]);
 }
+
+#[test]
+fn excluded_words() {
+    use std::iter::FromIterator;
+    
+    let dict = crate::build_dictionary(vec!["lore", "love"]).unwrap();
+    let exclude = vec!["love".to_owned()].into_iter().collect();
+    
+    let line = [
+        Square::Filled(LetterTile::Letter(Letter(b'l'))),
+        Square::Filled(LetterTile::Letter(Letter(b'o'))),
+        Square::Empty,
+        Square::Filled(LetterTile::Letter(Letter(b'e'))),
+    ];
+    
+    assert_eq!(
+        find_restriction(&line, 2, &dict, &exclude),
+        RestrictedSquare::Empty(LetterSet::from_iter(vec![Letter(b'r')])),
+    );
+}
@@ -1,15 +1,17 @@
 
 use super::{Table, Move, Placement, Direction, Square};
-use crate::{LetterScoring, BoardBonus};
-use crate::score_rules::ScoreRules;
+use crate::{Board, OwnedMove, LetterScoring, BoardBonus};
+use crate::score_rules::{ScoreRules, PremiumMode};
 
 /// Compute the score of a single move
 ///
 /// This is named naive, as a more efficient method could be implemented by
 /// computing parts of score in common with other words only once instead of
 /// again for each word
+///
+/// The letter scores are read from `ScoreRules::letter_scores`, not asked to `scoring`
 pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<impl LetterScoring, impl BoardBonus>) -> u32 {
-    let scoring = &score_rules.scoring;
+    let scoring = score_rules.letter_scores();
     let bonuses = &score_rules.bonuses;
     
     match play {
@@ -18,50 +20,56 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
             let tile = *tile;
             // count the score of the other letters for the vertical word
             // count the score of the other letters for the horizontal word
-            // add twice the score of the letter multiplied by bonus
+            // add the score of the letter multiplied by bonus once per word formed
             // add all, and multiply by bonus
             
             let mut v_score = 0;
+            let mut has_v_word = false;
             let mut v_place_back = Placement(pos, Direction::Vertical);
-            loop {
-                v_place_back = v_place_back.back();
-                match table.get(v_place_back.0) {
+            while let Some(place) = v_place_back.try_back(table.len_in(Direction::Vertical)) {
+                v_place_back = place;
+                match table.get(place.0) {
                     Some(Square::Filled(tile)) => {
-                        v_score += scoring.score_for(&tile);
+                        v_score += scoring.score_for(tile);
+                        has_v_word = true;
                     },
-                    _ => break // out of board, or no more letters
+                    _ => break // no more letters
                 }
             }
             let mut v_place_next = Placement(pos, Direction::Vertical);
-            loop {
-                v_place_next = v_place_next.next();
-                match table.get(v_place_next.0) {
+            while let Some(place) = v_place_next.try_next(table.len_in(Direction::Vertical)) {
+                v_place_next = place;
+                match table.get(place.0) {
                     Some(Square::Filled(tile)) => {
-                        v_score += scoring.score_for(&tile);
+                        v_score += scoring.score_for(tile);
+                        has_v_word = true;
                     },
-                    _ => break // out of board, or no more letters
+                    _ => break // no more letters
                 }
             }
             
             let mut h_score = 0;
+            let mut has_h_word = false;
             let mut h_place_back = Placement(pos, Direction::Horizontal);
-            loop {
-                h_place_back = h_place_back.back();
-                match table.get(h_place_back.0) {
+            while let Some(place) = h_place_back.try_back(table.len_in(Direction::Horizontal)) {
+                h_place_back = place;
+                match table.get(place.0) {
                     Some(Square::Filled(tile)) => {
-                        h_score += scoring.score_for(&tile);
+                        h_score += scoring.score_for(tile);
+                        has_h_word = true;
                     },
-                    _ => break // out of board, or no more letters
+                    _ => break // no more letters
                 }
             }
             let mut h_place_next = Placement(pos, Direction::Horizontal);
-            loop {
-                h_place_next = h_place_next.next();
-                match table.get(h_place_next.0) {
+            while let Some(place) = h_place_next.try_next(table.len_in(Direction::Horizontal)) {
+                h_place_next = place;
+                match table.get(place.0) {
                     Some(Square::Filled(tile)) => {
-                        h_score += scoring.score_for(&tile);
+                        h_score += scoring.score_for(tile);
+                        has_h_word = true;
                     },
-                    _ => break // out of board, or no more letters
+                    _ => break // no more letters
                 }
             }
             
@@ -69,7 +77,10 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
             
             let bonus = bonuses.bonus_at(pos);
             
-            (v_score + h_score + 2 * letter_score * bonus.letter) * bonus.word
+            // a tile touching no other tile still makes a (one letter) word
+            let n_words = (u32::from(has_v_word) + u32::from(has_h_word)).max(1);
+            
+            (v_score + h_score + n_words * letter_score * bonus.letter) * bonus.word
         },
         Move::MultiLetters(place, first, others) => {
             let place = *place;
@@ -83,7 +94,7 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
             
             let mut perp_score = 0;
             
-            let mut current_place = place.clone();
+            let mut current_place = place;
             let mut current_tile = first;
             let mut others_iter = others.iter().cloned();
             
@@ -91,25 +102,25 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
                 let mut local_score = 0;
                 let mut has_local_word = false;
                 let mut local_place_back = Placement(current_place.0, current_place.1.perp());
-                loop {
-                    local_place_back = local_place_back.back();
-                    match table.get(local_place_back.0) {
+                while let Some(place) = local_place_back.try_back(table.len_in(local_place_back.1)) {
+                    local_place_back = place;
+                    match table.get(place.0) {
                         Some(Square::Filled(tile)) => {
-                            local_score += scoring.score_for(&tile);
+                            local_score += scoring.score_for(tile);
                             has_local_word = true;
                         },
-                        _ => break // out of board, or no more letters
+                        _ => break // no more letters
                     }
                 }
                 let mut local_place_next = Placement(current_place.0, current_place.1.perp());
-                loop {
-                    local_place_next = local_place_next.next();
-                    match table.get(local_place_next.0) {
+                while let Some(place) = local_place_next.try_next(table.len_in(local_place_next.1)) {
+                    local_place_next = place;
+                    match table.get(place.0) {
                         Some(Square::Filled(tile)) => {
-                            local_score += scoring.score_for(&tile);
+                            local_score += scoring.score_for(tile);
                             has_local_word = true;
                         },
-                        _ => break // out of board, or no more letters
+                        _ => break // no more letters
                     }
                 }
                 
@@ -134,9 +145,9 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
             let mut word_score = 0;
             let mut word_multiplier = 1;
             
-            let mut begin_word = place.clone();
+            let mut begin_word = place;
             let mut step = 0;
-            while let Some(Square::Filled(_)) = table.get(begin_word.back().0) {
+            while let Some(Square::Filled(_)) = begin_word.try_back(table.len_in(place.1)).and_then(|back| table.get(back.0)) {
                 begin_word = begin_word.back();
                 step += 1;
             }
@@ -152,17 +163,20 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
                         if let Some((_, s)) = next_move_tile {
                             assert!(s != 0);
                         }
-                        word_score += scoring.score_for(&tile);
+                        word_score += scoring.score_for(tile);
                     },
                     Some(Square::Empty) => {
                         match &next_move_tile {
                             None => break,
                             Some((tile, step)) => {
                                 assert_eq!(*step, 0);
-                                let score = scoring.score_for(&tile);
+                                let score = scoring.score_for(tile);
                                 let bonus = bonuses.bonus_at(current_place.0);
                                 word_score += score * bonus.letter;
-                                word_multiplier *= bonus.word;
+                                match score_rules.premium_mode {
+                                    PremiumMode::Multiplicative => word_multiplier *= bonus.word,
+                                    PremiumMode::Additive => word_multiplier += bonus.word - 1,
+                                }
                             }
                         }
                     },
@@ -172,17 +186,242 @@ pub fn naive_score(table: &Table<Square>, play: &Move, score_rules: &ScoreRules<
                 current_place = current_place.next();
                 next_move_tile = next_move_tile.and_then(|(tile, step)| {
                     if step == 0 {
-                        match others_iter.next() {
-                            Some((step, tile)) => Some((tile, step)),
-                            None => None
-                        }
+                        others_iter.next().map(|(step, tile)| (tile, step))
                     } else {
                         Some((tile, step - 1))
                     }
                 });
             }
             
-            word_score * word_multiplier + perp_score + if others.len() == 6 { score_rules.extra_bonus } else { 0 }
+            if let Some(max) = score_rules.max_word_multiplier {
+                word_multiplier = word_multiplier.min(max);
+            }
+            
+            word_score * word_multiplier + perp_score + if play.tiles_placed() == score_rules.rack_size { score_rules.extra_bonus } else { 0 }
         },
     }
 }
+
+/// The score of the move on the board, where `words` are the words it plays
+/// (the ones in the direction of the move, or in any direction for a single tile)
+///
+/// Without `blank_scores_as_letter`, this is `naive_score` on the values of the board.
+/// With it, the wildcards of the move score as the letter they have in the word giving
+/// the best score, and the ones on the board as their letter when it is known
+pub fn score_with_words(board: &Board, play: &Move, words: &[&str], score_rules: &ScoreRules<impl LetterScoring, impl BoardBonus>) -> u32 {
+    if !score_rules.blank_scores_as_letter {
+        return naive_score(&board.value_table, play, score_rules)
+    }
+    
+    words.iter()
+    .filter_map(|word| crate::resolved_tiles(board, play, word))
+    .map(|tiles| {
+        let resolved = match play {
+            Move::SingleLetter(pos, _) => OwnedMove::SingleLetter(*pos, tiles[0]),
+            Move::MultiLetters(place, _, others) => OwnedMove::MultiLetters(
+                *place,
+                tiles[0],
+                others.iter().zip(&tiles[1..]).map(|(&(step, _), &tile)| (step, tile)).collect(),
+            ),
+        };
+        score_owned(&board.letter_table, &resolved, score_rules)
+    })
+    .max()
+    .unwrap_or_else(|| naive_score(&board.letter_table, play, score_rules))
+}
+
+/// Same as `naive_score`, for a move that is not allocated in an arena
+pub fn score_owned(table: &Table<Square>, play: &OwnedMove, score_rules: &ScoreRules<impl LetterScoring, impl BoardBonus>) -> u32 {
+    naive_score(table, &play.as_move(), score_rules)
+}
+
+#[test]
+fn bingo_bonus_after_word_multiplier() {
+    use crate::{Position, LetterTile, Letter};
+    
+    let score_rules = crate::test_utils::score_rules();
+    
+    let mut table = Table::fill_with(Square::Empty);
+    // cross word with the letter played on the triple word square
+    table.set(Position { row: 1, col: 0 }, Square::Filled(LetterTile::Letter(Letter(b'e'))));
+    
+    let tile = |l: u8| LetterTile::Letter(Letter(l));
+    let others = [
+        (0, tile(b'b')),
+        (0, tile(b'c')),
+        (0, tile(b'd')),
+        (0, tile(b'e')),
+        (0, tile(b'f')),
+        (0, tile(b'g')),
+    ];
+    // "abcdefg" on the first row, starting on the triple word square, 'd' on a double letter square
+    let play = Move::MultiLetters(
+        Placement(Position { row: 0, col: 0 }, Direction::Horizontal),
+        tile(b'a'),
+        &others,
+    );
+    
+    let word_value = 1 + 3 + 4 + 2 * 2 + 1 + 4 + 2;
+    let cross = (1 + 1) * 3;
+    assert_eq!(naive_score(&table, &play, &score_rules), 3 * word_value + cross + 50);
+    
+    // not a bingo with trays of eight tiles
+    let mut score_rules = score_rules;
+    score_rules.rack_size = 8;
+    assert_eq!(naive_score(&table, &play, &score_rules), 3 * word_value + cross);
+}
+
+#[test]
+fn capped_word_multiplier() {
+    use crate::{Position, LetterTile, Letter};
+    
+    let mut score_rules = crate::test_utils::score_rules();
+    
+    // the first row filled with 'e', except on the triple word squares
+    let mut table = Table::fill_with(Square::Empty);
+    for col in (1..7).chain(8..14) {
+        table.set(Position { row: 0, col }, Square::Filled(LetterTile::Letter(Letter(b'e'))));
+    }
+    
+    let tile = LetterTile::Letter(Letter(b'a'));
+    // 'a' on the two first triple word squares, the word has 14 letters worth 1
+    let two_squares = Move::MultiLetters(
+        Placement(Position { row: 0, col: 0 }, Direction::Horizontal),
+        tile,
+        &[(6, tile)],
+    );
+    // 'a' on the three triple word squares, 15 letters worth 1
+    let three_squares = Move::MultiLetters(
+        Placement(Position { row: 0, col: 0 }, Direction::Horizontal),
+        tile,
+        &[(6, tile), (6, tile)],
+    );
+    assert_eq!(naive_score(&table, &two_squares, &score_rules), 14 * 9);
+    assert_eq!(naive_score(&table, &three_squares, &score_rules), 15 * 27);
+    
+    score_rules.max_word_multiplier = Some(9);
+    assert_eq!(naive_score(&table, &two_squares, &score_rules), 14 * 9);
+    assert_eq!(naive_score(&table, &three_squares, &score_rules), 15 * 9);
+}
+
+#[test]
+fn additive_word_bonuses() {
+    use crate::{Position, LetterTile, Letter};
+    
+    let mut score_rules = crate::test_utils::score_rules();
+    
+    // the second row filled with 'e', except on the two double word squares
+    let mut table = Table::fill_with(Square::Empty);
+    for col in 2..13 {
+        table.set(Position { row: 1, col }, Square::Filled(LetterTile::Letter(Letter(b'e'))));
+    }
+    
+    let tile = LetterTile::Letter(Letter(b'a'));
+    let play = Move::MultiLetters(
+        Placement(Position { row: 1, col: 1 }, Direction::Horizontal),
+        tile,
+        &[(11, tile)],
+    );
+    assert_eq!(naive_score(&table, &play, &score_rules), 13 * 4);
+    
+    score_rules.premium_mode = PremiumMode::Additive;
+    assert_eq!(naive_score(&table, &play, &score_rules), 13 * 3);
+}
+
+#[test]
+fn premiums_under_old_tiles() {
+    use crate::{Position, LetterTile, Letter};
+    
+    let score_rules = crate::test_utils::score_rules();
+    let tile = |l: u8| LetterTile::Letter(Letter(l));
+    
+    // an 'e' on the triple word square of the corner, an 'x' on the double letter square of the first row
+    let mut table = Table::fill_with(Square::Empty);
+    table.set(Position { row: 0, col: 0 }, Square::Filled(tile(b'e')));
+    table.set(Position { row: 0, col: 3 }, Square::Filled(tile(b'x')));
+    
+    // "ab" below the 'e', the 'b' on a double word square, and "ea" downwards without the triple word
+    let play = Move::MultiLetters(
+        Placement(Position { row: 1, col: 0 }, Direction::Horizontal),
+        tile(b'a'),
+        &[(0, tile(b'b'))],
+    );
+    assert_eq!(naive_score(&table, &play, &score_rules), (1 + 3) * 2 + (1 + 1));
+    
+    // "eaaxa" on the first row, through both old tiles
+    let play = Move::MultiLetters(
+        Placement(Position { row: 0, col: 1 }, Direction::Horizontal),
+        tile(b'a'),
+        &[(0, tile(b'a')), (1, tile(b'a'))],
+    );
+    assert_eq!(naive_score(&table, &play, &score_rules), 1 + 1 + 1 + 8 + 1);
+    
+    // a single tile below the 'e' only forms "ea"
+    let play = Move::SingleLetter(Position { row: 1, col: 0 }, tile(b'a'));
+    assert_eq!(naive_score(&table, &play, &score_rules), 1 + 1);
+    
+    // a single tile on the double word square, forming "ea" in both directions
+    let mut table = Table::fill_with(Square::Empty);
+    table.set(Position { row: 0, col: 1 }, Square::Filled(tile(b'e')));
+    let play = Move::SingleLetter(Position { row: 1, col: 1 }, tile(b'a'));
+    assert_eq!(naive_score(&table, &play, &score_rules), (1 + 1) * 2);
+    table.set(Position { row: 1, col: 0 }, Square::Filled(tile(b'e')));
+    assert_eq!(naive_score(&table, &play, &score_rules), (1 + 1) * 2 + (1 + 1) * 2);
+}
+
+#[test]
+fn blank_as_its_letter() {
+    use crate::{Position, Letter, LetterTile, BoardFormat, Strictness};
+    
+    let mut score_rules = crate::test_utils::score_rules();
+    
+    // a wildcard played as the q of "qi", and an uppercase wildcard on the board used as an s
+    let board = Board::parse("\n\n\n\n\n\n\n  i\n  S\n", &BoardFormat::default(), Strictness::Strict).unwrap();
+    let play = Move::SingleLetter(Position { row: 7, col: 1 }, LetterTile::Wildcard);
+    assert_eq!(score_with_words(&board, &play, &["qi"], &score_rules), 1);
+    score_rules.blank_scores_as_letter = true;
+    assert_eq!(score_with_words(&board, &play, &["qi"], &score_rules), 10 + 1);
+    
+    let play = Move::SingleLetter(Position { row: 9, col: 2 }, LetterTile::Letter(Letter(b'h')));
+    assert_eq!(score_with_words(&board, &play, &["ish"], &score_rules), 1 + 1 + 4);
+    score_rules.blank_scores_as_letter = false;
+    assert_eq!(score_with_words(&board, &play, &["ish"], &score_rules), 1 + 4);
+}
+
+#[test]
+fn other_board_sizes() {
+    use crate::{Position, LetterTile, Letter};
+    use crate::score_rules::{GridBonus, EnglishScrabbleScoring};
+    
+    let tile = |l: u8| LetterTile::Letter(Letter(l));
+    // no bonus but a double word square in the last corner
+    let score_rules = |rows: usize, cols: usize| {
+        let mut grid = vec![".".repeat(cols); rows];
+        grid[rows - 1].replace_range(cols - 1.., "D");
+        ScoreRules::new(EnglishScrabbleScoring, GridBonus::parse(&grid).unwrap(), 50)
+    };
+    
+    // 15 rows of 11 squares, "cat" ending in the corner and "ot" downwards
+    let mut table = Table::with_size(15, 11, Square::Empty);
+    table.set(Position { row: 14, col: 8 }, Square::Filled(tile(b'c')));
+    table.set(Position { row: 13, col: 10 }, Square::Filled(tile(b'o')));
+    let play = Move::MultiLetters(
+        Placement(Position { row: 14, col: 9 }, Direction::Horizontal),
+        tile(b'a'),
+        &[(0, tile(b't'))],
+    );
+    assert_eq!(naive_score(&table, &play, &score_rules(15, 11)), (4 + 1 + 1) * 2 + (1 + 1) * 2);
+    
+    // 21×21, the old tiles before the 16th column are part of the word
+    let mut table = Table::with_size(21, 21, Square::Empty);
+    table.set(Position { row: 20, col: 17 }, Square::Filled(tile(b'c')));
+    table.set(Position { row: 20, col: 18 }, Square::Filled(tile(b'a')));
+    let play = Move::MultiLetters(
+        Placement(Position { row: 20, col: 19 }, Direction::Horizontal),
+        tile(b't'),
+        &[(0, tile(b's'))],
+    );
+    assert_eq!(naive_score(&table, &play, &score_rules(21, 21)), (4 + 1 + 1 + 1) * 2);
+    let play = Move::SingleLetter(Position { row: 20, col: 19 }, tile(b't'));
+    assert_eq!(naive_score(&table, &play, &score_rules(21, 21)), 4 + 1 + 1);
+}
@@ -1,21 +1,50 @@
 
 use std::rc::Rc;
 use std::fmt;
+use std::convert::TryInto;
 
 use fst::Automaton;
 
-use super::{RestrictedSquare, LetterTile, Letter};
+use super::{RestrictedSquare, RestrictedLine, LetterTile, Letter};
+use crate::{Alphabet, Move, Strictness, UnknownCharacter, BYTES_VERSION};
 
-#[derive(Clone)]
+/// The letters a tray can hold
+static ALPHABET: Alphabet = Alphabet::english();
+
+/// The number of letters of `ALPHABET`
+const N_LETTERS: usize = 26;
+
+/// The index of the letter in `TrayRemaining::letters`, None if the tray cannot hold it
+fn letter_index(letter: u8) -> Option<usize> {
+    ALPHABET.index(Letter(letter))
+}
+
+/// The tiles left in a tray
+///
+/// It is copied for each letter tried while browsing the dictionary, so it only
+/// counts the letters of `ALPHABET`, from `a` to `z`
+#[derive(Clone, Copy)]
 pub struct TrayRemaining {
-    letters: [u8; 256],
+    letters: [u8; N_LETTERS],
     n_wildcards: u8,
     /// The total number of remaining letters+wildcards to play
     n_total: u32,
 }
 
 impl TrayRemaining {
-    pub fn new(letters: [u8; 256], n_wildcards: u8) -> TrayRemaining {
+    /// The tray with the number of each letter (indexed by its byte) and of wildcards
+    ///
+    /// Fails on the first letter the tray cannot hold, the ones from `a` to `z` being held
+    pub fn new(letters: [u8; 256], n_wildcards: u8) -> Result<TrayRemaining, Letter> {
+        let mut counts = [0; N_LETTERS];
+        for (l, &n) in letters.iter().enumerate().filter(|(_, &n)| n > 0) {
+            let i = letter_index(l as u8).ok_or(Letter(l as u8))?;
+            counts[i] = n;
+        }
+        Ok(Self::from_counts(counts, n_wildcards))
+    }
+    
+    fn from_counts(letters: [u8; N_LETTERS], n_wildcards: u8) -> TrayRemaining {
         let n_total = letters.iter().map(|&i| i as u32).sum::<u32>() + n_wildcards as u32;
         TrayRemaining {
             letters,
@@ -23,14 +52,148 @@ impl TrayRemaining {
             n_total,
         }
     }
+    
+    /// The number of tiles of the letter in the tray
+    pub fn count(&self, letter: u8) -> u8 {
+        letter_index(letter).map_or(0, |i| self.letters[i])
+    }
+}
+
+impl TrayRemaining {
+    /// Reads the tray from the letters it contains, where stars are wildcards
+    ///
+    /// The letters can be in uppercase or lowercase. Unlike the board, an uppercase
+    /// letter is not a wildcard: the letter of a wildcard is only chosen when it is played
+    pub fn parse(tray: &str, strictness: Strictness) -> Result<TrayRemaining, Vec<UnknownCharacter>> {
+        let mut letters = [0u8; N_LETTERS];
+        let mut wild_count = 0;
+        let mut errors = vec![];
+        
+        for (i, byte) in tray.bytes().enumerate() {
+            if let Some(l) = letter_index(byte.to_ascii_lowercase()) {
+                letters[l] += 1;
+            } else if byte == b'*' {
+                wild_count += 1;
+            } else {
+                match strictness {
+                    Strictness::Lenient => log::warn!("a byte in the given tray is neither a letter or a wildcard (*): {}", byte),
+                    Strictness::Strict => errors.push(UnknownCharacter { byte, line: 0, column: i }),
+                }
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(TrayRemaining::from_counts(letters, wild_count))
+        } else {
+            Err(errors)
+        }
+    }
+    
+    /// Reads the tray from pairs of a letter (or a star for wildcards) and its count, like `e5a2*1`
+    ///
+    /// Fails on the first character that is not in such a pair, or whose count is too big
+    pub fn from_compact_str(tray: &str) -> Result<TrayRemaining, UnknownCharacter> {
+        let mut letters = [0u8; N_LETTERS];
+        let mut wild_count = 0u8;
+        
+        let bytes = tray.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            let error = UnknownCharacter { byte, line: 0, column: i };
+            
+            let count = if let Some(l) = letter_index(byte.to_ascii_lowercase()) {
+                &mut letters[l]
+            } else if byte == b'*' {
+                &mut wild_count
+            } else {
+                return Err(error)
+            };
+            
+            let digits = bytes[i+1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            let n = tray[i+1..i+1+digits].parse::<u8>().map_err(|_| error)?;
+            *count = count.checked_add(n).ok_or(error)?;
+            
+            i += 1 + digits;
+        }
+        
+        Ok(TrayRemaining::from_counts(letters, wild_count))
+    }
+    
+    const BYTES_MAGIC: u8 = b'T';
+    
+    /// A compact encoding of the tray, that can be read with `from_bytes`
+    ///
+    /// After a magic byte and the version, the number of each letter from `a` to `z`,
+    /// then the number of wildcards. As a tray only holds these letters, it is never None
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let mut bytes = vec![Self::BYTES_MAGIC, BYTES_VERSION];
+        bytes.extend_from_slice(&self.letters);
+        bytes.push(self.n_wildcards);
+        Some(bytes)
+    }
+    
+    /// Reads a tray encoded with `to_bytes`, None if the bytes are not valid
+    pub fn from_bytes(bytes: &[u8]) -> Option<TrayRemaining> {
+        match bytes {
+            &[Self::BYTES_MAGIC, BYTES_VERSION, ref counts @ .., n_wildcards] => {
+                Some(TrayRemaining::from_counts(counts.try_into().ok()?, n_wildcards))
+            },
+            _ => None,
+        }
+    }
+    
+    /// All the tiles of the tray, letters first
+    pub fn tiles(&self) -> impl Iterator<Item=LetterTile> + '_ {
+        ALPHABET.letters().zip(self.letters).flat_map(|(l, n)| {
+            std::iter::repeat_n(LetterTile::Letter(l), n as usize)
+        }).chain(std::iter::repeat_n(LetterTile::Wildcard, self.n_wildcards as usize))
+    }
+}
+
+/// The vowels of `TrayRemaining::vowel_consonant_counts`
+pub const ENGLISH_VOWELS: &[u8] = b"aeiou";
+
+impl TrayRemaining {
+    /// The number of vowels (a, e, i, o, u) and of consonants of the tray, wildcards count as neither
+    pub fn vowel_consonant_counts(&self) -> (u32, u32) {
+        self.vowel_consonant_counts_with(ENGLISH_VOWELS)
+    }
+    
+    /// Same as `vowel_consonant_counts`, with the (lowercase) letters counted as vowels
+    pub fn vowel_consonant_counts_with(&self, vowels: &[u8]) -> (u32, u32) {
+        let n_vowels = vowels.iter().map(|&l| self.count(l) as u32).sum::<u32>();
+        let n_letters = self.n_total - self.n_wildcards as u32;
+        (n_vowels, n_letters - n_vowels)
+    }
+}
+
+impl TrayRemaining {
+    /// The probability to get at least one tile of `letter` (None for a wildcard) when
+    /// drawing `draws` tiles from this bag
+    ///
+    /// With `k` such tiles among the `n` of the bag, this is 1 minus the hypergeometric
+    /// probability of drawing none of them: (n-k)/n × (n-k-1)/(n-1) × …
+    pub fn draw_probability(&self, letter: Option<Letter>, draws: usize) -> f64 {
+        let k = match letter {
+            Some(Letter(l)) => self.count(l),
+            None => self.n_wildcards,
+        } as usize;
+        let n = self.n_total as usize;
+        
+        let none_drawn = (0..draws.min(n)).map(|i| {
+            (n - i).saturating_sub(k) as f64 / (n - i) as f64
+        }).product::<f64>();
+        1. - none_drawn
+    }
 }
 
 impl fmt::Debug for TrayRemaining {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // we will only print letters
-        for l in b'a'..=b'z' {
-            for _ in 0..self.letters[l as usize] {
-                write!(f, "{}", l as char)?;
+        for (l, &n) in ALPHABET.letters().zip(&self.letters) {
+            for _ in 0..n {
+                write!(f, "{}", l)?;
             }
         }
         for _ in 0..self.n_wildcards {
@@ -42,18 +205,19 @@ impl fmt::Debug for TrayRemaining {
 
 impl TrayRemaining {
     pub fn remove(&self, letter: u8) -> Option<TrayRemaining> {
-        if self.letters[letter as usize] > 0 {
-            let mut tmp = self.clone();
-            tmp.letters[letter as usize] -= 1;
-            tmp.n_total -= 1;
-            Some(tmp)
-        } else {
-            None
+        match letter_index(letter) {
+            Some(l) if self.letters[l] > 0 => {
+                let mut tmp = *self;
+                tmp.letters[l] -= 1;
+                tmp.n_total -= 1;
+                Some(tmp)
+            },
+            _ => None,
         }
     }
     pub fn remove_wildcard(&self) -> Option<TrayRemaining> {
         if self.n_wildcards > 0 {
-            let mut tmp = self.clone();
+            let mut tmp = *self;
             tmp.n_wildcards -= 1;
             tmp.n_total -= 1;
             Some(tmp)
@@ -61,6 +225,52 @@ impl TrayRemaining {
             None
         }
     }
+    
+    /// What remains in the tray once the tiles of `mov` are played
+    ///
+    /// None if the tray does not contain them
+    pub fn after_move(&self, mov: &Move) -> Option<TrayRemaining> {
+        mov.placed_tiles().try_fold(*self, |tray, tile| match tile {
+            LetterTile::Letter(Letter(l)) => tray.remove(l),
+            LetterTile::Wildcard => tray.remove_wildcard(),
+        })
+    }
+    
+    /// The tray without the `held` tiles, to find the moves that keep them for a later turn
+    ///
+    /// The held tiles that are not in the tray are ignored
+    pub fn without(&self, held: &TrayRemaining) -> TrayRemaining {
+        held.tiles().fold(*self, |tray, tile| match tile {
+            LetterTile::Letter(Letter(l)) => tray.remove(l),
+            LetterTile::Wildcard => tray.remove_wildcard(),
+        }.unwrap_or(tray))
+    }
+}
+
+/// An automaton accepting the non-empty words that can be made with tiles of the tray, in any order
+///
+/// The wildcards are only used for the letters missing from the tray
+#[derive(Debug, Clone)]
+pub struct TilesAutomaton {
+    pub tray: TrayRemaining,
+}
+
+impl Automaton for TilesAutomaton {
+    /// What is left in the tray
+    type State = Option<TrayRemaining>;
+    
+    fn start(&self) -> Self::State {
+        Some(self.tray)
+    }
+    fn is_match(&self, state: &Self::State) -> bool {
+        state.is_some_and(|tray| tray.n_total < self.tray.n_total)
+    }
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        state.and_then(|tray| tray.remove(byte).or_else(|| tray.remove_wildcard()))
+    }
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -75,12 +285,15 @@ pub enum WildcardAssignment {
     Intersection(usize),
     /// Value of the missing letter
     MissingLetter(u8),
+    /// Value of a letter that is neither in the tray nor replaced by a wildcard
+    /// (see `ScrabbleAutomata::missing_budget`)
+    NotInTray(u8),
 }
 
 #[derive(Debug, Clone)]
-pub struct ScrabbleAutomata<'line> {
-    /// The line slice that starts at the begin of the word
-    pub line: &'line [RestrictedSquare],
+pub struct ScrabbleAutomata<Line> {
+    /// The line that starts at the begin of the word
+    pub line: Line,
     /// What there is in the tray
     pub tray: TrayRemaining,
     /// The required length for a word to be attached
@@ -91,6 +304,17 @@ pub struct ScrabbleAutomata<'line> {
     /// This only applies to wildcards in the move being created, wildcards on
     /// the board are always interpreted as signifying anything
     pub wildcards_have_multi_meaning: bool,
+    /// The minimum length of the word allowed by the rules
+    pub min_word_len: usize,
+    /// The number of each letter of the tray that must be played
+    pub must_play: [u8; 256],
+    /// The number of tiles of the tray the word must be played with, if only the bingos are allowed
+    pub bingo_tiles: Option<usize>,
+    /// The most tiles of the tray the word can be played with
+    pub max_tiles_placed: Option<usize>,
+    /// How many letters the word can use without having them in the tray, to
+    /// find the words that are a few tiles short (0 for the playable words)
+    pub missing_budget: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -101,34 +325,50 @@ pub struct ScrabbleAutomataState {
     pub wildcards: WildcardAssignmentList,
     /// What is left in the tray
     pub tray: TrayRemaining,
+    /// How many letters were used without being in the tray
+    pub n_missing: u8,
 }
 
-impl<'line> Automaton for ScrabbleAutomata<'line> {
+impl<Line: RestrictedLine> ScrabbleAutomata<Line> {
+    /// The number of tiles played to reach `state`, the missing ones included
+    fn n_played(&self, state: &ScrabbleAutomataState) -> usize {
+        (self.tray.n_total - state.tray.n_total) as usize + state.n_missing as usize
+    }
+}
+
+impl<Line: RestrictedLine> Automaton for ScrabbleAutomata<Line> {
     type State = Option<ScrabbleAutomataState>;
     
     fn start(&self) -> Self::State {
         Some(ScrabbleAutomataState {
             position: 0,
             wildcards: WildcardAssignmentList::Empty,
-            tray: self.tray.clone(),
+            tray: self.tray,
+            n_missing: 0,
         })
     }
     
     fn is_match(&self, state: &Self::State) -> bool {
         if let Some(state) = state {
-            if let Some(RestrictedSquare::Filled(_)) = self.line.get(state.position) {
+            if let Some(RestrictedSquare::Filled(_)) = self.line.square(state.position) {
                 // there is a letter where the word continues
                 false
             } else {
-                if self.tray.n_total == state.tray.n_total {
+                if self.n_played(state) == 0 {
                     // we have not played a single thing
                     false
                 } else {
-                    if state.position < self.min_len {
-                        // the word is too short to be attached
+                    if state.position < self.min_len || state.position < self.min_word_len {
+                        // the word is too short to be attached, or to be allowed
+                        false
+                    } else if self.bingo_tiles.is_some_and(|n| self.n_played(state) != n) {
+                        // the tiles of the board are not counted, only the ones played
                         false
                     } else {
-                        true
+                        // the required letters have been played
+                        self.must_play.iter().enumerate().all(|(l, &n)| {
+                            self.tray.count(l as u8) - state.tray.count(l as u8) >= n
+                        })
                     }
                 }
             }
@@ -139,7 +379,7 @@ impl<'line> Automaton for ScrabbleAutomata<'line> {
     
     fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
         state.as_ref().and_then(|state| {
-            match self.line.get(state.position) {
+            match self.line.square(state.position) {
                 // we are out of the board
                 None => None,
                 Some(spot) => match spot {
@@ -147,14 +387,16 @@ impl<'line> Automaton for ScrabbleAutomata<'line> {
                     RestrictedSquare::Filled(LetterTile::Wildcard) => Some(ScrabbleAutomataState {
                         position: state.position + 1,
                         wildcards: state.wildcards.clone(),
-                        tray: state.tray.clone(),
+                        tray: state.tray,
+                        n_missing: state.n_missing,
                     }),
                     // letter on the board must match what we accept
-                    &RestrictedSquare::Filled(LetterTile::Letter(l)) => if l == Letter(byte) {
+                    RestrictedSquare::Filled(LetterTile::Letter(l)) => if l == Letter(byte) {
                         Some(ScrabbleAutomataState {
                             position: state.position + 1,
                             wildcards: state.wildcards.clone(),
-                            tray: state.tray.clone(),
+                            tray: state.tray,
+                            n_missing: state.n_missing,
                         })
                     } else {
                         None
@@ -175,6 +417,9 @@ impl<'line> Automaton for ScrabbleAutomata<'line> {
                                     // this is a missing letter
                                     (Some(tray), Some(WildcardAssignment::MissingLetter(byte))))
                                 )
+                                // the letter is not in the tray at all
+                                .or((state.n_missing < self.missing_budget)
+                                    .then_some((Some(state.tray), Some(WildcardAssignment::NotInTray(byte)))))
                                 .unwrap_or((None, None))
                             } else {
                                 if self.wildcards_have_multi_meaning {
@@ -188,6 +433,9 @@ impl<'line> Automaton for ScrabbleAutomata<'line> {
                                     (None, None)
                                 }
                             };
+                            // the tiles of the board are not counted, only the ones played
+                            let n_missing = state.n_missing
+                                + matches!(wildcard_assignment, Some(WildcardAssignment::NotInTray(_))) as u8;
                             new_tray.map(|tray| ScrabbleAutomataState {
                                 position: state.position + 1,
                                 wildcards: if let Some(assig) = wildcard_assignment {
@@ -195,8 +443,10 @@ impl<'line> Automaton for ScrabbleAutomata<'line> {
                                 } else {
                                     state.wildcards.clone()
                                 },
-                                tray: tray,
+                                tray,
+                                n_missing,
                             })
+                            .filter(|state| self.max_tiles_placed.is_none_or(|max| self.n_played(state) <= max))
                         }
                     },
                 }
@@ -233,26 +483,31 @@ fn test() {
             LetterSet::any(),
         ),
     ];
-
+    
     let automaton = ScrabbleAutomata {
         line: &line[..],
         tray: TrayRemaining {
-            letters: [1; 256],
+            letters: [1; N_LETTERS],
             n_wildcards: 1,
-            n_total: 257,
+            n_total: 27,
         },
         min_len: 0,
         wildcards_have_multi_meaning: true,
+        min_word_len: 0,
+        must_play: [0; 256],
+        bingo_tiles: None,
+        max_tiles_placed: None,
+        missing_budget: 0,
     };
-
+    
     dbg!(&automaton);
-
+    
     let mut build = fst::SetBuilder::memory();
     build.insert(b"tepa").unwrap();
     let dict = build.into_set();
-
+    
     use fst::{Streamer, IntoStreamer};
-
+    
     let mut x = dict.search_with_state(automaton).into_stream();
     
     let mut acc = vec![];
@@ -270,4 +525,84 @@ fn test() {
         acc[0].1.wildcards,
         WildcardAssignmentList::Elem(WildcardAssignment::Intersection(2), Rc::new(WildcardAssignmentList::Empty)),
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn tray_after_move() {
+    use crate::{Placement, Position, Direction};
+    
+    let tray = TrayRemaining::parse("cat*", Strictness::Strict).unwrap();
+    let others = [(0, LetterTile::Wildcard), (1, LetterTile::Letter(Letter(b't')))];
+    let mov = Move::MultiLetters(
+        Placement(Position { row: 7, col: 7 }, Direction::Horizontal),
+        LetterTile::Letter(Letter(b'c')),
+        &others,
+    );
+    
+    assert_eq!(format!("{:?}", tray.after_move(&mov).unwrap()), "a[1 letters]");
+    assert!(tray.after_move(&Move::SingleLetter(Position { row: 7, col: 7 }, LetterTile::Letter(Letter(b'z')))).is_none());
+}
+
+#[test]
+fn compact_tray() {
+    let tray = TrayRemaining::from_compact_str("e5A2*1").unwrap();
+    assert_eq!(format!("{:?}", tray), "aaeeeee*[8 letters]");
+    
+    assert_eq!(TrayRemaining::from_compact_str("e5a").unwrap_err(), UnknownCharacter { byte: b'a', line: 0, column: 2 });
+    assert_eq!(TrayRemaining::from_compact_str("5e").unwrap_err(), UnknownCharacter { byte: b'5', line: 0, column: 0 });
+    assert_eq!(TrayRemaining::from_compact_str("e200e100").unwrap_err(), UnknownCharacter { byte: b'e', line: 0, column: 4 });
+}
+
+#[test]
+fn tray_of_counts() {
+    let mut letters = [0; 256];
+    letters[b'z' as usize] = 2;
+    letters[b'a' as usize] = 1;
+    let tray = TrayRemaining::new(letters, 1).unwrap();
+    assert_eq!(format!("{:?}", tray), "azz*[4 letters]");
+    assert_eq!(tray.tiles().collect::<Vec<_>>(), [
+        LetterTile::Letter(Letter(b'a')),
+        LetterTile::Letter(Letter(b'z')),
+        LetterTile::Letter(Letter(b'z')),
+        LetterTile::Wildcard,
+    ]);
+    
+    // the tray cannot hold the other letters
+    letters[b'E' as usize] = 1;
+    assert_eq!(TrayRemaining::new(letters, 1).unwrap_err(), Letter(b'E'));
+}
+
+#[test]
+fn vowels_and_consonants() {
+    let tray = TrayRemaining::parse("aeyrst*", Strictness::Strict).unwrap();
+    assert_eq!(tray.vowel_consonant_counts(), (2, 4));
+    assert_eq!(tray.vowel_consonant_counts_with(b"aeiouy"), (3, 3));
+}
+
+#[test]
+fn draw_probabilities() {
+    let bag = TrayRemaining::from_compact_str("a9b2e12q1z1*2").unwrap();
+    let n = 27.;
+    let close = |p: f64, expected: f64| assert!((p - expected).abs() < 1e-12, "{} != {}", p, expected);
+    
+    close(bag.draw_probability(None, 1), 2. / n);
+    // 1 - C(25, 3) / C(27, 3)
+    close(bag.draw_probability(None, 3), 1. - (25. * 24. * 23.) / (27. * 26. * 25.));
+    close(bag.draw_probability(Some(Letter(b'q')), 7), 7. / n);
+    // 1 - C(15, 2) / C(27, 2)
+    close(bag.draw_probability(Some(Letter(b'e')), 2), 1. - (15. * 14.) / (27. * 26.));
+    
+    close(bag.draw_probability(Some(Letter(b'c')), 7), 0.);
+    close(bag.draw_probability(Some(Letter(b'a')), 0), 0.);
+    // not enough tiles of the bag without an a to draw
+    close(bag.draw_probability(Some(Letter(b'a')), 19), 1.);
+    close(bag.draw_probability(Some(Letter(b'z')), 100), 1.);
+}
+
+#[test]
+fn mixed_case_tray() {
+    // the uppercase letters are not wildcards, unlike on the board
+    let tray = TrayRemaining::parse("CaT*", Strictness::Strict).unwrap();
+    assert_eq!(format!("{:?}", tray), "act*[4 letters]");
+    assert_eq!(tray.count(b'c'), 1);
+}
@@ -0,0 +1,36 @@
+//! The setup shared by the tests
+
+use super::{Board, LetterTile, Rules};
+use super::score_rules::{ScoreRules, EnglishScrabbleScoring, ScrabbleBonus};
+use super::solver::{evaluate, EvaluationResult, StrList};
+use super::solver::arenas::Arenas;
+use super::solver::word_finder::TrayRemaining;
+use super::{LetterScoring, BoardBonus};
+
+/// The scores of English Scrabble
+pub fn score_rules() -> ScoreRules<EnglishScrabbleScoring, ScrabbleBonus> {
+    ScoreRules::new(EnglishScrabbleScoring, ScrabbleBonus, 50)
+}
+
+/// The usual rules of English Scrabble, with a dictionary of these words
+pub fn rules(words: &[&str]) -> Rules<EnglishScrabbleScoring, ScrabbleBonus, Vec<u8>> {
+    Rules::new(crate::build_dictionary(words).unwrap(), score_rules())
+}
+
+/// The arenas the results of a solve borrow from
+#[derive(Default)]
+pub struct TestArenas<'a> {
+    pub strs: Arenas<u8>,
+    pub str_lists: Arenas<StrList<'a>>,
+    pub moves: Arenas<(usize, LetterTile)>,
+}
+
+impl<'a> TestArenas<'a> {
+    pub fn evaluate(
+        &'a self,
+        tray: &TrayRemaining, board: &Board,
+        rules: Rules<impl LetterScoring, impl BoardBonus, impl AsRef<[u8]> + Sync>,
+    ) -> EvaluationResult<'a> {
+        evaluate(&self.strs, &self.str_lists, &self.moves, tray, board, rules)
+    }
+}
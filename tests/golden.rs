@@ -0,0 +1,94 @@
+//! Snapshots of the moves found for a few positions, through the public API
+
+use scrabble::{Board, BoardFormat, Rules, Strictness};
+use scrabble::solver::{SolverContext, RulesOverrides};
+use scrabble::solver::word_finder::TrayRemaining;
+
+#[test]
+fn golden_positions() {
+    let dictionary = scrabble::build_dictionary(["at", "as", "ta", "act", "acts", "cat", "cats", "sat", "scat", "tact"]).unwrap();
+    let rules = Rules::new(dictionary, scrabble::presets::scrabble_english().score_rules);
+    let context = SolverContext::new(rules);
+    let solve = |board: &str, tray: &str| {
+        let board = Board::parse(board, &BoardFormat::default(), Strictness::Strict).unwrap();
+        let tray = TrayRemaining::parse(tray, Strictness::Strict).unwrap();
+        // the snapshots leave out the spaces aligning the scores
+        format!("{:?}", context.solve(&tray, &board, &RulesOverrides::default())).lines()
+        .map(|line| format!("{}\n", line.trim_start()))
+        .collect::<String>()
+    };
+    
+    // first move with a wildcard, on the double word square of the center
+    assert_eq!(solve("", "ta*"), "\
+  2: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(a), [(0, Wildcard)]) [\"as\"]\n\
+  2: MultiLetters(Placement(Position { row: 7, col: 6 }, Horizontal), Letter(a), [(0, Wildcard)]) [\"as\"]\n\
+  2: MultiLetters(Placement(Position { row: 7, col: 7 }, Vertical), Letter(a), [(0, Wildcard)]) [\"as\"]\n\
+  2: MultiLetters(Placement(Position { row: 7, col: 7 }, Horizontal), Letter(a), [(0, Wildcard)]) [\"as\"]\n\
+  4: MultiLetters(Placement(Position { row: 5, col: 7 }, Vertical), Wildcard, [(0, Letter(a)), (0, Letter(t))]) [\"cat\", \"sat\"]\n\
+  4: MultiLetters(Placement(Position { row: 5, col: 7 }, Vertical), Letter(a), [(0, Wildcard), (0, Letter(t))]) [\"act\"]\n\
+  4: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Wildcard, [(0, Letter(a)), (0, Letter(t))]) [\"cat\", \"sat\"]\n\
+  4: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(a), [(0, Wildcard), (0, Letter(t))]) [\"act\"]\n\
+  4: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(a), [(0, Letter(t))]) [\"at\"]\n\
+  4: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(t), [(0, Letter(a))]) [\"ta\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 5 }, Horizontal), Wildcard, [(0, Letter(a)), (0, Letter(t))]) [\"cat\", \"sat\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 5 }, Horizontal), Letter(a), [(0, Wildcard), (0, Letter(t))]) [\"act\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 6 }, Horizontal), Wildcard, [(0, Letter(a)), (0, Letter(t))]) [\"cat\", \"sat\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 6 }, Horizontal), Letter(a), [(0, Wildcard), (0, Letter(t))]) [\"act\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 6 }, Horizontal), Letter(a), [(0, Letter(t))]) [\"at\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 6 }, Horizontal), Letter(t), [(0, Letter(a))]) [\"ta\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Vertical), Wildcard, [(0, Letter(a)), (0, Letter(t))]) [\"cat\", \"sat\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Vertical), Letter(a), [(0, Wildcard), (0, Letter(t))]) [\"act\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Vertical), Letter(a), [(0, Letter(t))]) [\"at\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Vertical), Letter(t), [(0, Letter(a))]) [\"ta\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Horizontal), Wildcard, [(0, Letter(a)), (0, Letter(t))]) [\"cat\", \"sat\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Horizontal), Letter(a), [(0, Wildcard), (0, Letter(t))]) [\"act\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Horizontal), Letter(a), [(0, Letter(t))]) [\"at\"]\n\
+  4: MultiLetters(Placement(Position { row: 7, col: 7 }, Horizontal), Letter(t), [(0, Letter(a))]) [\"ta\"]\n\
+");
+    // hooks and parallel plays forming cross words, some on double letter squares
+    assert_eq!(solve("\n\n\n\n\n\n\n       cat\n", "tsa"), "\
+  2: SingleLetter(Position { row: 6, col: 9 }, Letter(a)) [\"at\"]\n\
+  2: SingleLetter(Position { row: 8, col: 9 }, Letter(a)) [\"ta\"]\n\
+  3: SingleLetter(Position { row: 6, col: 8 }, Letter(t)) [\"ta\"]\n\
+  3: SingleLetter(Position { row: 8, col: 8 }, Letter(s)) [\"as\"]\n\
+  3: SingleLetter(Position { row: 8, col: 8 }, Letter(t)) [\"at\"]\n\
+  4: MultiLetters(Placement(Position { row: 6, col: 9 }, Horizontal), Letter(a), [(0, Letter(s))]) [\"as\"]\n\
+  4: MultiLetters(Placement(Position { row: 6, col: 9 }, Horizontal), Letter(a), [(0, Letter(t))]) [\"at\"]\n\
+  4: MultiLetters(Placement(Position { row: 8, col: 9 }, Horizontal), Letter(a), [(0, Letter(s))]) [\"as\"]\n\
+  4: MultiLetters(Placement(Position { row: 8, col: 9 }, Horizontal), Letter(a), [(0, Letter(t))]) [\"at\"]\n\
+  5: MultiLetters(Placement(Position { row: 5, col: 9 }, Vertical), Letter(s), [(0, Letter(a))]) [\"sat\"]\n\
+  5: MultiLetters(Placement(Position { row: 6, col: 8 }, Vertical), Letter(s), [(1, Letter(t))]) [\"sat\"]\n\
+  6: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(a), [(1, Letter(t))]) [\"act\"]\n\
+  6: MultiLetters(Placement(Position { row: 8, col: 7 }, Vertical), Letter(a), [(0, Letter(t))]) [\"cat\"]\n\
+  7: SingleLetter(Position { row: 7, col: 6 }, Letter(s)) [\"scat\"]\n\
+  7: SingleLetter(Position { row: 7, col: 10 }, Letter(s)) [\"cats\"]\n\
+  7: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(a), [(1, Letter(t)), (0, Letter(s))]) [\"acts\"]\n\
+  7: MultiLetters(Placement(Position { row: 6, col: 7 }, Vertical), Letter(s), [(1, Letter(a)), (0, Letter(t))]) [\"scat\"]\n\
+  7: MultiLetters(Placement(Position { row: 8, col: 7 }, Vertical), Letter(a), [(0, Letter(t)), (0, Letter(s))]) [\"cats\"]\n\
+  8: MultiLetters(Placement(Position { row: 6, col: 8 }, Horizontal), Letter(t), [(0, Letter(a))]) [\"ta\"]\n\
+  8: MultiLetters(Placement(Position { row: 8, col: 8 }, Horizontal), Letter(t), [(0, Letter(a))]) [\"ta\"]\n\
+  9: MultiLetters(Placement(Position { row: 6, col: 10 }, Vertical), Letter(a), [(0, Letter(s))]) [\"as\"]\n\
+  9: MultiLetters(Placement(Position { row: 8, col: 8 }, Horizontal), Letter(s), [(0, Letter(a)), (0, Letter(t))]) [\"sat\"]\n\
+ 10: MultiLetters(Placement(Position { row: 6, col: 6 }, Vertical), Letter(a), [(0, Letter(s))]) [\"as\"]\n\
+ 10: MultiLetters(Placement(Position { row: 7, col: 10 }, Vertical), Letter(s), [(0, Letter(a)), (0, Letter(t))]) [\"sat\"]\n\
+ 11: MultiLetters(Placement(Position { row: 7, col: 6 }, Vertical), Letter(s), [(0, Letter(a)), (0, Letter(t))]) [\"sat\"]\n\
+");
+    // a wildcard and a letter around several words
+    assert_eq!(solve("\n\n\n\n\n\n\n       cat\n        c\n        t\n", "s*"), "\
+  1: SingleLetter(Position { row: 6, col: 9 }, Wildcard) [\"at\"]\n\
+  1: SingleLetter(Position { row: 9, col: 7 }, Wildcard) [\"at\"]\n\
+  1: SingleLetter(Position { row: 9, col: 9 }, Wildcard) [\"ta\"]\n\
+  2: MultiLetters(Placement(Position { row: 6, col: 9 }, Horizontal), Wildcard, [(0, Letter(s))]) [\"as\"]\n\
+  2: MultiLetters(Placement(Position { row: 9, col: 6 }, Horizontal), Letter(s), [(0, Wildcard)]) [\"sat\"]\n\
+  2: MultiLetters(Placement(Position { row: 9, col: 7 }, Vertical), Wildcard, [(0, Letter(s))]) [\"as\"]\n\
+  2: MultiLetters(Placement(Position { row: 9, col: 9 }, Vertical), Wildcard, [(0, Letter(s))]) [\"as\"]\n\
+  4: MultiLetters(Placement(Position { row: 5, col: 9 }, Vertical), Letter(s), [(0, Wildcard)]) [\"sat\"]\n\
+  6: SingleLetter(Position { row: 6, col: 8 }, Wildcard) [\"tact\"]\n\
+  7: SingleLetter(Position { row: 7, col: 6 }, Letter(s)) [\"scat\"]\n\
+  7: SingleLetter(Position { row: 7, col: 10 }, Letter(s)) [\"cats\"]\n\
+  7: SingleLetter(Position { row: 10, col: 8 }, Letter(s)) [\"acts\"]\n\
+  8: MultiLetters(Placement(Position { row: 6, col: 6 }, Vertical), Wildcard, [(0, Letter(s))]) [\"as\"]\n\
+  8: MultiLetters(Placement(Position { row: 6, col: 10 }, Vertical), Wildcard, [(0, Letter(s))]) [\"as\"]\n\
+  8: MultiLetters(Placement(Position { row: 10, col: 7 }, Horizontal), Wildcard, [(0, Letter(s))]) [\"as\"]\n\
+");
+}